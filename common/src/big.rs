@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: u32 = 9;
+
+// Unsigned integer of any size, stored as base 10^9 limbs with the lowest limb first so
+// decimal digit math stays cheap. There are never leading zero limbs, zero has no limbs
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        BigUint { limbs }
+    }
+
+    pub fn digit_count(&self) -> u32 {
+        match self.limbs.last() {
+            Some(&top) => (self.limbs.len() as u32 - 1) * BASE_DIGITS + top.checked_ilog10().map_or(1, |digits| digits + 1),
+            None => 1,
+        }
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0;
+
+        for index in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = carry
+                + self.limbs.get(index).copied().unwrap_or(0) as u64
+                + other.limbs.get(index).copied().unwrap_or(0) as u64;
+            limbs.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        limbs.push(carry as u32);
+
+        BigUint::normalized(limbs)
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0_u64; self.limbs.len() + other.limbs.len() + 1];
+
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = limbs[i + j] + a as u64 * b as u64 + carry;
+                limbs[i + j] = product % BASE;
+                carry = product / BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+
+        BigUint::normalized(limbs.into_iter().map(|limb| limb as u32).collect())
+    }
+
+    // digits of self followed by digits of other
+    pub fn concat(&self, other: &BigUint) -> BigUint {
+        let shift = other.digit_count();
+        let mut limbs = vec![0; (shift / BASE_DIGITS) as usize];
+        limbs.extend(&self.limbs);
+
+        let shifted = BigUint::normalized(limbs).mul(&BigUint::from(10_u64.pow(shift % BASE_DIGITS)));
+        shifted.add(other)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint::normalized(vec![(value % BASE) as u32, (value / BASE % BASE) as u32, (value / BASE / BASE) as u32])
+    }
+}
+
+impl From<u8> for BigUint {
+    fn from(value: u8) -> Self {
+        BigUint::from(value as u64)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected only decimal digits")
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseBigUintError);
+        }
+
+        // limbs are read from the end of the text, 9 digits at a time
+        let limbs = text
+            .as_bytes()
+            .rchunks(BASE_DIGITS as usize)
+            .map(|chunk| chunk.iter().fold(0, |limb, byte| limb * 10 + (byte - b'0') as u32))
+            .collect();
+
+        Ok(BigUint::normalized(limbs))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((top, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+
+        write!(f, "{}", top)?;
+        for limb in rest.iter().rev() {
+            write!(f, "{:09}", limb)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_test() {
+        let big = |text: &str| text.parse::<BigUint>().unwrap();
+        assert_eq!(
+            big("99999999999999999999").mul(&big("99999999999999999999")).to_string(),
+            "9999999999999999999800000000000000000001"
+        );
+        assert_eq!(big("999999999").add(&big("1")).to_string(), "1000000000");
+        assert_eq!(big("120").concat(&big("0")).to_string(), "1200");
+        assert_eq!(big("0").concat(&big("1000000000")).to_string(), "1000000000");
+        assert_eq!(big("000123").to_string(), "123");
+        assert_eq!(big("1000000000").digit_count(), 10);
+        assert!(big("1000000000") > big("999999999"));
+        assert!("12a".parse::<BigUint>().is_err());
+    }
+}
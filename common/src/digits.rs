@@ -0,0 +1,164 @@
+// Exact base 10 digit math on unsigned integers, without going through floats or strings
+pub trait Digits: Sized + Copy {
+    // number of decimal digits, zero counts as one digit
+    fn digit_count(self) -> u32;
+
+    // 10^exponent, None if it does not fit
+    fn pow10(exponent: u32) -> Option<Self>;
+
+    // digits of self followed by digits of other, None on overflow
+    fn concat(self, other: Self) -> Option<Self>;
+
+    // splits after the first index digits, so 1234 at 1 gives (1, 234).
+    // None if index is past the last digit
+    fn split_at_digit(self, index: u32) -> Option<(Self, Self)>;
+
+    // undoes concat, so 12345 without 345 gives 12. None if self does not end in suffix's digits
+    fn strip_digit_suffix(self, suffix: Self) -> Option<Self>;
+}
+
+macro_rules! impl_digits {
+    ($($t:ty),*) => {
+        $(
+            impl Digits for $t {
+                fn digit_count(self) -> u32 {
+                    self.checked_ilog10().map_or(1, |digits| digits + 1)
+                }
+
+                fn pow10(exponent: u32) -> Option<Self> {
+                    (10 as $t).checked_pow(exponent)
+                }
+
+                fn concat(self, other: Self) -> Option<Self> {
+                    let shift = Self::pow10(other.digit_count())?;
+                    self.checked_mul(shift)?.checked_add(other)
+                }
+
+                fn split_at_digit(self, index: u32) -> Option<(Self, Self)> {
+                    let digits = self.digit_count();
+                    if index > digits {
+                        return None;
+                    }
+
+                    match Self::pow10(digits - index) {
+                        Some(divisor) => Some((self / divisor, self % divisor)),
+                        None => Some((0, self)),
+                    }
+                }
+
+                fn strip_digit_suffix(self, suffix: Self) -> Option<Self> {
+                    match Self::pow10(suffix.digit_count()) {
+                        Some(divisor) => (self % divisor == suffix).then(|| self / divisor),
+                        None => (self == suffix).then_some(0),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_digits!(u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_count_test() {
+        assert_eq!(0_u64.digit_count(), 1);
+        assert_eq!(0_u128.digit_count(), 1);
+        assert_eq!(u64::MAX.digit_count(), 20);
+        assert_eq!(u128::MAX.digit_count(), 39);
+
+        for exponent in 1..=19 {
+            let power = u64::pow10(exponent).unwrap();
+            assert_eq!((power - 1).digit_count(), exponent);
+            assert_eq!(power.digit_count(), exponent + 1);
+            assert_eq!((power + 1).digit_count(), exponent + 1);
+        }
+
+        for exponent in 1..=38 {
+            let power = u128::pow10(exponent).unwrap();
+            assert_eq!((power - 1).digit_count(), exponent);
+            assert_eq!(power.digit_count(), exponent + 1);
+            assert_eq!((power + 1).digit_count(), exponent + 1);
+        }
+
+        assert_eq!(u64::pow10(20), None);
+        assert_eq!(u128::pow10(39), None);
+    }
+
+    #[test]
+    fn concat_test() {
+        assert_eq!(12_u64.concat(345), Some(12345));
+        assert_eq!(1_u64.concat(0), Some(10));
+        assert_eq!(0_u64.concat(7), Some(7));
+
+        for exponent in 1..=19 {
+            let power = u64::pow10(exponent).unwrap();
+            assert_eq!(1_u64.concat(power - 1).map(u64::digit_count), (exponent < 19).then_some(exponent + 1));
+            assert_eq!((power - 1).concat(9), (power - 1).checked_mul(10).map(|v| v + 9));
+        }
+
+        for exponent in 1..=38 {
+            let power = u128::pow10(exponent).unwrap();
+            assert_eq!(1_u128.concat(power - 1).map(u128::digit_count), Some(exponent + 1));
+            assert_eq!((power - 1).concat(9), (power - 1).checked_mul(10).map(|v| v + 9));
+        }
+
+        assert_eq!(1_u64.concat(u64::MAX), None);
+        assert_eq!(u64::MAX.concat(0), None);
+        assert_eq!(1844674407370955161_u64.concat(5), Some(u64::MAX));
+        assert_eq!(1844674407370955161_u64.concat(6), None);
+        assert_eq!(1_u128.concat(u128::MAX), None);
+    }
+
+    #[test]
+    fn split_at_digit_test() {
+        assert_eq!(1234_u64.split_at_digit(2), Some((12, 34)));
+        assert_eq!(1234_u64.split_at_digit(1), Some((1, 234)));
+        assert_eq!(1234_u64.split_at_digit(0), Some((0, 1234)));
+        assert_eq!(1234_u64.split_at_digit(4), Some((1234, 0)));
+        assert_eq!(1234_u64.split_at_digit(5), None);
+        assert_eq!(1000_u64.split_at_digit(2), Some((10, 0)));
+        assert_eq!(u64::MAX.split_at_digit(0), Some((0, u64::MAX)));
+        assert_eq!(u128::MAX.split_at_digit(0), Some((0, u128::MAX)));
+
+        for exponent in 1..=19 {
+            let power = u64::pow10(exponent).unwrap();
+            for index in 0..=exponent + 1 {
+                let (first, second) = power.split_at_digit(index).unwrap();
+                assert_eq!(first, if index == 0 { 0 } else { u64::pow10(index - 1).unwrap() });
+                assert_eq!(second, if index == 0 { power } else { 0 });
+            }
+
+            let (first, second) = (power - 1).split_at_digit(exponent / 2).unwrap();
+            assert_eq!(first.concat(second), Some(power - 1));
+        }
+
+        for exponent in 2..=38 {
+            let power = u128::pow10(exponent).unwrap();
+            let (first, second) = (power - 1).split_at_digit(exponent / 2).unwrap();
+            assert_eq!(first.digit_count() + second.digit_count(), exponent);
+            assert_eq!(first.concat(second), Some(power - 1));
+        }
+    }
+
+    #[test]
+    fn strip_digit_suffix_test() {
+        assert_eq!(12345_u64.strip_digit_suffix(345), Some(12));
+        assert_eq!(12345_u64.strip_digit_suffix(45), Some(123));
+        assert_eq!(12345_u64.strip_digit_suffix(12345), Some(0));
+        assert_eq!(12345_u64.strip_digit_suffix(346), None);
+        assert_eq!(45_u64.strip_digit_suffix(345), None);
+        assert_eq!(100_u64.strip_digit_suffix(0), Some(10));
+        assert_eq!(101_u64.strip_digit_suffix(0), None);
+        assert_eq!(u64::MAX.strip_digit_suffix(u64::MAX), Some(0));
+        assert_eq!(u128::MAX.strip_digit_suffix(u128::MAX), Some(0));
+        assert_eq!(u128::MAX.strip_digit_suffix(455), Some(u128::MAX / 1000));
+
+        for (first, second) in [(1_u64, 0_u64), (7, 99), (1844674407370955161, 5), (0, 123)] {
+            assert_eq!(first.concat(second).unwrap().strip_digit_suffix(second), Some(first));
+        }
+    }
+}
@@ -4,6 +4,11 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+pub mod big;
+pub mod digits;
+pub mod parallel;
+pub mod random;
+
 pub fn parse_to_text(input_file_name: &str) -> Result<String, Box<dyn Error>> {
     let path = Path::new("inputs").join(input_file_name);
     let content = fs::read_to_string(path)?;
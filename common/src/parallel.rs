@@ -0,0 +1,111 @@
+// Maps over independent items, across threads when the parallel feature is enabled and in
+// order on one thread otherwise. Either way results come back in the order of the items, so
+// answers never depend on how the work was split
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 0 means not set, so the count comes from AOC_THREADS or the number of available cores
+static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_thread_count(threads: usize) {
+    THREAD_COUNT.store(threads, Ordering::Relaxed);
+}
+
+pub fn thread_count() -> usize {
+    match THREAD_COUNT.load(Ordering::Relaxed) {
+        0 => std::env::var("AOC_THREADS")
+            .ok()
+            .and_then(|threads| threads.parse().ok())
+            .filter(|&threads| threads > 0)
+            .or_else(|| std::thread::available_parallelism().ok().map(|threads| threads.get()))
+            .unwrap_or(1),
+        threads => threads,
+    }
+}
+
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    map_init(items, || (), |_, item| f(item))
+}
+
+// Like map, with scratch state made once per thread by init and handed to every call on it
+pub fn map_init<T, S, R, I, F>(items: &[T], init: I, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    I: Fn() -> S + Sync,
+    F: Fn(&mut S, &T) -> R + Sync,
+{
+    if cfg!(feature = "parallel") {
+        map_on_threads(items, thread_count(), init, f)
+    } else {
+        let mut state = init();
+        items.iter().map(|item| f(&mut state, item)).collect()
+    }
+}
+
+// each thread takes one contiguous chunk, so joining the chunks in order keeps item order
+fn map_on_threads<T, S, R, I, F>(items: &[T], threads: usize, init: I, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    I: Fn() -> S + Sync,
+    F: Fn(&mut S, &T) -> R + Sync,
+{
+    let chunk_size = items.len().div_ceil(threads.max(1)).max(1);
+    let (init, f) = (&init, &f);
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut state = init();
+                    chunk.iter().map(|item| f(&mut state, item)).collect::<Vec<R>>()
+                })
+            })
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_keeps_order_test() {
+        let items: Vec<u64> = (0..1000).collect();
+        let expected: Vec<u64> = items.iter().map(|item| item * item).collect();
+
+        assert_eq!(map(&items, |item| item * item), expected);
+        for threads in [1, 2, 3, 7, 64, 2000] {
+            assert_eq!(map_on_threads(&items, threads, || (), |_, item| item * item), expected);
+        }
+        assert!(map_on_threads(&[] as &[u64], 4, || (), |_, item| *item).is_empty());
+    }
+
+    #[test]
+    fn map_init_state_test() {
+        let items: Vec<usize> = (0..100).collect();
+
+        // every call sees state made on its own thread, counting calls seen so far
+        let counts = map_on_threads(&items, 4, || 0, |calls: &mut usize, _| {
+            *calls += 1;
+            *calls
+        });
+        assert_eq!(counts, (0..4).flat_map(|_| 1..=25).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn thread_count_test() {
+        set_thread_count(3);
+        assert_eq!(thread_count(), 3);
+        set_thread_count(0);
+        assert!(thread_count() >= 1);
+    }
+}
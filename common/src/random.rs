@@ -0,0 +1,34 @@
+// Small seeded generator for tests that need many varied inputs, the same seed always gives
+// the same sequence. Not suitable for anything that has to be unpredictable
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    // the next number in 0..limit, limit must not be 0
+    pub fn below(&mut self, limit: u64) -> u64 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) % limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_rng_test() {
+        let first: Vec<u64> = (0..100).scan(SeededRng::new(7), |rng, _| Some(rng.below(10))).collect();
+        let again: Vec<u64> = (0..100).scan(SeededRng::new(7), |rng, _| Some(rng.below(10))).collect();
+        let other: Vec<u64> = (0..100).scan(SeededRng::new(8), |rng, _| Some(rng.below(10))).collect();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert!(first.iter().all(|&value| value < 10));
+        assert!((0..10).all(|digit| first.contains(&digit)));
+    }
+}
@@ -1,22 +1,22 @@
-use common::parse_to_array;
+use common::{parallel, parse_to_array};
 
-fn is_increasing(levels: &Vec<i32>) -> bool {
-    let mut sorted_levels: Vec<i32> = levels.clone();
+fn is_increasing(levels: &[i32]) -> bool {
+    let mut sorted_levels: Vec<i32> = levels.to_vec();
     sorted_levels.sort();
 
-    levels == &sorted_levels
+    levels == sorted_levels
 }
 
-fn is_decreasing(levels: &Vec<i32>) -> bool {
-    let mut reverse_sorted_levels: Vec<i32> = levels.clone();
+fn is_decreasing(levels: &[i32]) -> bool {
+    let mut reverse_sorted_levels: Vec<i32> = levels.to_vec();
     reverse_sorted_levels.sort();
     reverse_sorted_levels.reverse();
 
-    levels == &reverse_sorted_levels
+    levels == reverse_sorted_levels
 }
 
 // Safe if levels are either all decreasing or all increasing, with no step greater than 3 or less than 1
-fn is_safe(levels: &Vec<i32>) -> bool {
+fn is_safe(levels: &[i32]) -> bool {
     if !is_increasing(levels) && !is_decreasing(levels) {
         return false;
     }
@@ -27,7 +27,7 @@ fn is_safe(levels: &Vec<i32>) -> bool {
 
         let difference = current.abs_diff(next);
 
-        if difference > 3 || difference < 1 {
+        if !(1..=3).contains(&difference) {
             return false;
         }
     }
@@ -35,7 +35,7 @@ fn is_safe(levels: &Vec<i32>) -> bool {
     true
 }
 
-fn safe_reports_count(reports: &Vec<Vec<i32>>) -> usize {
+fn safe_reports_count(reports: &[Vec<i32>]) -> usize {
     let mut count = 0;
 
     for report in reports {
@@ -48,19 +48,14 @@ fn safe_reports_count(reports: &Vec<Vec<i32>>) -> usize {
 }
 
 // For each report, generate all permutations of one item removed
-fn generate_report_set(report: &Vec<i32>) -> Vec<Vec<i32>> {
+fn generate_report_set(report: &[i32]) -> Vec<Vec<i32>> {
     let mut set: Vec<Vec<i32>> = Vec::new();
 
     for i in 0..report.len() {
         let mut subset = Vec::new();
 
-        for j in 0..i {
-            subset.push(report[j]);
-        }
-
-        for j in i+1.. report.len() {
-            subset.push(report[j]);
-        }
+        subset.extend_from_slice(&report[..i]);
+        subset.extend_from_slice(&report[i + 1..]);
 
         set.push(subset);
     }
@@ -69,25 +64,19 @@ fn generate_report_set(report: &Vec<i32>) -> Vec<Vec<i32>> {
 }
 
 // Safe if any permutation for a given report passes original safe check
-fn dampened_safe_reports_count(reports: &Vec<Vec<i32>>) -> usize {
-    let mut count = 0;
-
-    for report in reports {
-        let set = generate_report_set(report);
-
-        for permutation in set {
-            if is_safe(&permutation) {
-                count += 1;
-                break;
-            }
-        }
-
-    }
+fn is_dampened_safe(report: &[i32]) -> bool {
+    generate_report_set(report).iter().any(|permutation| is_safe(permutation))
+}
 
-    count
+// reports are independent, so with the parallel feature they are checked across threads
+fn dampened_safe_reports_count(reports: &[Vec<i32>]) -> usize {
+    parallel::map(reports, |report| is_dampened_safe(report))
+        .into_iter()
+        .filter(|&safe| safe)
+        .count()
 }
 
-fn parse_reports(lines: &Vec<String>) -> Vec<Vec<i32>> {
+fn parse_reports(lines: &[String]) -> Vec<Vec<i32>> {
     let mut result = Vec::new();
 
     for line in lines {
@@ -1,15 +1,18 @@
+use crate::ordering::{order_update, OrderError};
+use crate::violations::{check_update, CheckMode};
 use std::collections::HashMap;
-use std::hash::Hash;
-use std::usize;
 use common::parse_to_array;
 
-fn split_input(input: &Vec<String>) -> (Vec<String>, Vec<String>) {
+mod ordering;
+mod violations;
+
+fn split_input(input: &[String]) -> (Vec<String>, Vec<String>) {
     let mut delimiter_found = false;
     let mut graph_input: Vec<String> = Vec::new();
     let mut updates_input: Vec<String> = Vec::new();
 
     for line in input {
-        if line == "" {
+        if line.is_empty() {
             delimiter_found = true;
             continue;
         }
@@ -24,7 +27,7 @@ fn split_input(input: &Vec<String>) -> (Vec<String>, Vec<String>) {
     (graph_input, updates_input)
 }
 
-fn parse_pairs(graph_input: &Vec<String>) -> Vec<(usize, usize)> {
+fn parse_pairs(graph_input: &[String]) -> Vec<(usize, usize)> {
     let mut output: Vec<(usize, usize)> = Vec::new();
 
     for line in graph_input {
@@ -43,13 +46,13 @@ fn build_graph(pairs: Vec<(usize, usize)>) -> HashMap<usize, Vec<usize>> {
     let mut graph: HashMap<usize, Vec<usize>> = HashMap::new();
 
     for (left, right) in pairs {
-        graph.entry(left).or_insert_with(Vec::new).push(right);
+        graph.entry(left).or_default().push(right);
     }
 
     graph
 }
 
-fn parse_updates(update_input: &Vec<String>) -> Vec<Vec<usize>> {
+fn parse_updates(update_input: &[String]) -> Vec<Vec<usize>> {
     let mut updates:Vec<Vec<usize>> = Vec::new();
 
     for line in update_input {
@@ -75,65 +78,39 @@ fn comparator(graph: &HashMap<usize, Vec<usize>>, left: &usize, right: &usize) -
     }
 }
 
-fn update_is_ordered_correctly(graph: &HashMap<usize, Vec<usize>>, update: &Vec<usize>) -> bool {
-    for i in 0..update.len() - 1{
-        let current = update.get(i).unwrap();
-
-        for j in i+1.. update.len() {
-            let next = update.get(j).unwrap();
-
-            if !comparator(graph, current, next) {
-                return false;
-            }
-        }
-    }
-
-    true
+// strict, every page needs a rule putting it before each page after it
+fn update_is_ordered_correctly(graph: &HashMap<usize, Vec<usize>>, update: &[usize]) -> bool {
+    check_update(graph, update, CheckMode::Strict).is_empty()
 }
 
-fn get_middle_element(update: &Vec<usize>) -> usize {
+fn get_middle_element(update: &[usize]) -> usize {
     *update.get(update.len() / 2).unwrap()
 }
 
-fn sum_correct_updates(graph: &HashMap<usize, Vec<usize>>, updates: &Vec<Vec<usize>>) -> usize {
+fn sum_correct_updates(graph: &HashMap<usize, Vec<usize>>, updates: &[Vec<usize>]) -> usize {
     let mut total: usize = 0;
 
     for update in updates {
-        if update_is_ordered_correctly(&graph, update) {
-            total += get_middle_element(&update);
+        if update_is_ordered_correctly(graph, update) {
+            total += get_middle_element(update);
         }
     }
 
     total
 }
 
-fn sort_incorrect_update(graph: &HashMap<usize, Vec<usize>>, update: &Vec<usize>) -> Vec<usize> {
-    let mut to_sort = update.clone();
-
-    to_sort.sort_by(|a, b| {
-        if comparator(graph, a, b) {
-            std::cmp::Ordering::Less
-        } else if comparator(graph, b, a) {
-            std::cmp::Ordering::Greater
-        } else {
-            std::cmp::Ordering::Equal
-        }
-    });
-
-    to_sort
-}
-
-fn sum_incorrect_updates(graph: &HashMap<usize, Vec<usize>>, updates: &Vec<Vec<usize>>) -> usize {
+// rules for the pages of an update that loop back on themselves leave no valid order
+fn sum_incorrect_updates(graph: &HashMap<usize, Vec<usize>>, updates: &[Vec<usize>]) -> Result<usize, OrderError> {
     let mut total: usize = 0;
 
     for update in updates {
-        if !update_is_ordered_correctly(&graph, update) {
-            let sorted_update = sort_incorrect_update(graph, update);
+        if !update_is_ordered_correctly(graph, update) {
+            let sorted_update = order_update(graph, update)?.pages;
             total += get_middle_element(&sorted_update);
         }
     }
 
-    total
+    Ok(total)
 }
 
 fn main() {
@@ -146,13 +123,14 @@ fn main() {
     let part_one = sum_correct_updates(&graph, &updates);
     println!("{}", part_one);
 
-    let part_two = sum_incorrect_updates(&graph, &updates);
+    let part_two = sum_incorrect_updates(&graph, &updates).unwrap();
     println!("{}", part_two);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::violations::{Violation, ViolationKind};
     use lazy_static::lazy_static;
 
     lazy_static! {
@@ -188,13 +166,13 @@ mod tests {
 
     #[test]
     fn ordering_test() {
-        assert_eq!(comparator(&GRAPH, &47, &53), true);
+        assert!(comparator(&GRAPH, &47, &53));
     }
 
     #[test]
     fn update_order_test() {
         let update = vec![75, 47, 61, 53, 29];
-        assert_eq!(update_is_ordered_correctly(&GRAPH, &update), true)
+        assert!(update_is_ordered_correctly(&GRAPH, &update))
     }
 
     #[test]
@@ -228,7 +206,114 @@ mod tests {
             vec![97, 13, 75, 29, 47],
         ];
 
-        assert_eq!(sum_incorrect_updates(&GRAPH, &updates), 123);
+        assert_eq!(sum_incorrect_updates(&GRAPH, &updates).unwrap(), 123);
+    }
+
+    #[test]
+    fn order_update_test() {
+        let order = order_update(&GRAPH, &[97, 13, 75, 29, 47]).unwrap();
+        assert_eq!(order.pages, vec![97, 75, 47, 29, 13]);
+        assert!(!order.is_ambiguous());
+
+        assert_eq!(order_update(&GRAPH, &[61, 13, 29]).unwrap().pages, vec![61, 29, 13]);
+        assert_eq!(order_update(&GRAPH, &[]).unwrap().pages, Vec::<usize>::new());
     }
 
+    #[test]
+    fn ambiguous_order_test() {
+        // nothing orders 12 against the rest, so it could go anywhere
+        let order = order_update(&GRAPH, &[13, 12, 47, 61]).unwrap();
+        assert_eq!(order.pages, vec![12, 47, 61, 13]);
+        assert_eq!(order.first_tie, Some(vec![12, 47]));
+
+        let graph = build_graph(parse_pairs(&["1|2".to_string(), "1|3".to_string()]));
+        let order = order_update(&graph, &[3, 2, 1]).unwrap();
+        assert_eq!(order.pages, vec![1, 3, 2]);
+        assert_eq!(order.first_tie, Some(vec![3, 2]));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let graph = build_graph(parse_pairs(&[
+            "1|2".to_string(),
+            "2|3".to_string(),
+            "3|1".to_string(),
+            "4|1".to_string(),
+        ]));
+
+        let error = order_update(&graph, &[4, 3, 2, 1]).unwrap_err();
+        let OrderError::Cycle { pages } = &error;
+        assert_eq!(pages.len(), 3);
+        for (before, after) in pages.iter().zip(pages.iter().cycle().skip(1)) {
+            assert!(comparator(&graph, before, after));
+        }
+        assert_eq!(error, OrderError::Cycle { pages: vec![1, 2, 3] });
+        assert_eq!(error.to_string(), "rules form a cycle: 1|2, 2|3, 3|1");
+
+        // the cycle only matters when all its pages are in the update
+        assert_eq!(order_update(&graph, &[3, 4, 1]).unwrap().pages, vec![3, 4, 1]);
+        assert_eq!(sum_incorrect_updates(&graph, &[vec![1, 2, 3]]), Err(error));
+    }
+
+    #[test]
+    fn violations_test() {
+        let violations = check_update(&GRAPH, &[75, 97, 47, 61, 53], CheckMode::Strict);
+        assert_eq!(
+            violations,
+            vec![Violation {
+                before: 75,
+                before_position: 0,
+                after: 97,
+                after_position: 1,
+                kind: ViolationKind::Contradicted,
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "page 75 at position 0 comes before page 97 at position 1, but rule 97|75 exists"
+        );
+
+        let violations = check_update(&GRAPH, &[97, 13, 75, 29, 47], CheckMode::NoContradiction);
+        let pairs: Vec<(usize, usize)> = violations.iter().map(|v| (v.before_position, v.after_position)).collect();
+        assert_eq!(pairs, vec![(1, 2), (1, 3), (1, 4), (3, 4)]);
+
+        assert!(check_update(&GRAPH, &[75, 47, 61, 53, 29], CheckMode::Strict).is_empty());
+    }
+
+    #[test]
+    fn check_modes_test() {
+        // no rule relates 12 to anything
+        let update = [75, 12, 47];
+
+        let strict = check_update(&GRAPH, &update, CheckMode::Strict);
+        assert_eq!(strict.len(), 2);
+        assert!(strict.iter().all(|v| v.kind == ViolationKind::MissingRule));
+        assert_eq!(
+            strict[0].to_string(),
+            "page 75 at position 0 comes before page 12 at position 1, but no rule 75|12 exists"
+        );
+        assert!(!update_is_ordered_correctly(&GRAPH, &update));
+
+        assert!(check_update(&GRAPH, &update, CheckMode::NoContradiction).is_empty());
+        assert_eq!(check_update(&GRAPH, &[47, 12, 75], CheckMode::NoContradiction).len(), 1);
+    }
+
+    #[test]
+    fn mutual_rules_test() {
+        // 1|2 and 2|1 both exist, strict mode only needs the first one like it always did
+        let graph = build_graph(vec![(1, 2), (2, 1), (1, 3), (2, 3)]);
+
+        assert!(check_update(&graph, &[1, 2, 3], CheckMode::Strict).is_empty());
+        assert!(update_is_ordered_correctly(&graph, &[1, 2, 3]));
+        assert!(update_is_ordered_correctly(&graph, &[2, 1]));
+
+        let violations = check_update(&graph, &[1, 2, 3], CheckMode::NoContradiction);
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].before, violations[0].after), (1, 2));
+        assert_eq!(violations[0].kind, ViolationKind::Contradicted);
+
+        // without the rule asked for, the other one still decides the kind
+        let kinds: Vec<ViolationKind> = check_update(&graph, &[3, 1, 4], CheckMode::Strict).iter().map(|v| v.kind).collect();
+        assert_eq!(kinds, vec![ViolationKind::Contradicted, ViolationKind::MissingRule, ViolationKind::MissingRule]);
+    }
 }
@@ -0,0 +1,120 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    // pages whose rules form a loop, each one has to come before the next and the last before the first
+    Cycle { pages: Vec<usize> },
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::Cycle { pages } => {
+                let rules: Vec<String> = pages
+                    .iter()
+                    .zip(pages.iter().cycle().skip(1))
+                    .map(|(before, after)| format!("{}|{}", before, after))
+                    .collect();
+                write!(f, "rules form a cycle: {}", rules.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for OrderError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageOrder {
+    pub pages: Vec<usize>,
+    // the first set of pages no rule puts in order, when there is more than one valid order.
+    // Tied pages keep the order they had in the update
+    pub first_tie: Option<Vec<usize>>,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl PageOrder {
+    pub fn is_ambiguous(&self) -> bool {
+        self.first_tie.is_some()
+    }
+}
+
+// Orders the pages of an update with Kahn's algorithm over only the rules between its pages
+pub fn order_update(graph: &HashMap<usize, Vec<usize>>, update: &[usize]) -> Result<PageOrder, OrderError> {
+    // edges run between positions in the update, so repeated pages stay separate
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); update.len()];
+    let mut incoming: Vec<usize> = vec![0; update.len()];
+
+    for (i, page) in update.iter().enumerate() {
+        let Some(after) = graph.get(page) else {
+            continue;
+        };
+
+        for (j, other) in update.iter().enumerate() {
+            if i != j && after.contains(other) {
+                successors[i].push(j);
+                incoming[j] += 1;
+            }
+        }
+    }
+
+    let mut ready: BTreeSet<usize> = (0..update.len()).filter(|&i| incoming[i] == 0).collect();
+    let mut pages = Vec::with_capacity(update.len());
+    let mut first_tie = None;
+
+    while let Some(i) = ready.pop_first() {
+        if first_tie.is_none() && !ready.is_empty() {
+            first_tie = Some(std::iter::once(i).chain(ready.iter().copied()).map(|j| update[j]).collect());
+        }
+
+        pages.push(update[i]);
+        for &j in &successors[i] {
+            incoming[j] -= 1;
+            if incoming[j] == 0 {
+                ready.insert(j);
+            }
+        }
+    }
+
+    if pages.len() < update.len() {
+        // starting from the smallest page so the same cycle is always reported the same way
+        let mut cycle: Vec<usize> = find_cycle(&successors, &incoming).into_iter().map(|i| update[i]).collect();
+        let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+        cycle.rotate_left(smallest);
+
+        return Err(OrderError::Cycle { pages: cycle });
+    }
+
+    Ok(PageOrder { pages, first_tie })
+}
+
+// Every position Kahn's algorithm left behind still has a predecessor that was also left
+// behind, so walking predecessors from any of them has to come back round
+fn find_cycle(successors: &[Vec<usize>], incoming: &[usize]) -> Vec<usize> {
+    let mut predecessor: Vec<Option<usize>> = vec![None; successors.len()];
+    for (i, after) in successors.iter().enumerate() {
+        if incoming[i] > 0 {
+            for &j in after {
+                if incoming[j] > 0 {
+                    predecessor[j] = Some(i);
+                }
+            }
+        }
+    }
+
+    let mut seen: Vec<Option<usize>> = vec![None; successors.len()];
+    let mut walk: Vec<usize> = Vec::new();
+    let mut current = (0..successors.len()).find(|&i| incoming[i] > 0).unwrap();
+
+    while seen[current].is_none() {
+        seen[current] = Some(walk.len());
+        walk.push(current);
+        current = predecessor[current].unwrap();
+    }
+
+    // walked against the rules, so reverse to read it in rule order
+    let mut cycle = walk.split_off(seen[current].unwrap());
+    cycle.reverse();
+    cycle
+}
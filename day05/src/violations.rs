@@ -0,0 +1,81 @@
+use crate::comparator;
+use std::collections::HashMap;
+use std::fmt;
+
+// Which pairs of pages an update has to have a rule for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMode {
+    // every page needs a rule putting it before each page after it
+    Strict,
+    // pages only need no rule putting them the other way round
+    #[cfg_attr(not(test), allow(dead_code))]
+    NoContradiction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    // the rule after|before exists
+    Contradicted,
+    // neither order has a rule, only a violation in strict mode
+    MissingRule,
+}
+
+// Two pages of an update in the order they appear, with their positions in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub before: usize,
+    pub before_position: usize,
+    pub after: usize,
+    pub after_position: usize,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ViolationKind::Contradicted => write!(
+                f,
+                "page {} at position {} comes before page {} at position {}, but rule {}|{} exists",
+                self.before, self.before_position, self.after, self.after_position, self.after, self.before
+            ),
+            ViolationKind::MissingRule => write!(
+                f,
+                "page {} at position {} comes before page {} at position {}, but no rule {}|{} exists",
+                self.before, self.before_position, self.after, self.after_position, self.before, self.after
+            ),
+        }
+    }
+}
+
+// Every pair of pages breaking the rules under the mode, ordered by position
+pub fn check_update(graph: &HashMap<usize, Vec<usize>>, update: &[usize], mode: CheckMode) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for (i, before) in update.iter().enumerate() {
+        for (j, after) in update.iter().enumerate().skip(i + 1) {
+            // strict mode only asks for the before|after rule, so a pair with rules both ways
+            // still passes there
+            if mode == CheckMode::Strict && comparator(graph, before, after) {
+                continue;
+            }
+
+            let kind = if comparator(graph, after, before) {
+                ViolationKind::Contradicted
+            } else if mode == CheckMode::Strict {
+                ViolationKind::MissingRule
+            } else {
+                continue;
+            };
+
+            violations.push(Violation {
+                before: *before,
+                before_position: i,
+                after: *after,
+                after_position: j,
+                kind,
+            });
+        }
+    }
+
+    violations
+}
@@ -23,6 +23,7 @@ impl Add for Coordinate {
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
 pub enum Direction{
     UP,
@@ -31,6 +32,19 @@ pub enum Direction{
     RIGHT
 }
 
+impl Direction {
+    // the guard glyph facing this way, ^ > v or <
+    pub fn from_glyph(glyph: char) -> Option<Direction> {
+        match glyph {
+            '^' => Some(Direction::UP),
+            '>' => Some(Direction::RIGHT),
+            'v' => Some(Direction::DOWN),
+            '<' => Some(Direction::LEFT),
+            _ => None,
+        }
+    }
+}
+
 pub struct Guard {
     pub direction: Direction,
     pub coordinate: Coordinate
@@ -63,20 +77,18 @@ impl Guard {
 pub struct Grid {
     pub contents: Vec<Vec<char>>,
     pub starting_position: Coordinate,
+    pub starting_direction: Direction,
+    // characters the guard turns at, everything else can be walked over
+    pub obstacles: Vec<char>,
 }
 
 impl Grid {
-    // pub fn get_starting_position(&self) -> Coordinate {
-    //     for y in 0..self.contents.len() {
-    //         for x in 0..self.contents[0].len() {
-    //             if self.contents[y][x] == '^' {
-    //                 return Coordinate::new(x as i32, y as i32)
-    //             }
-    //         }
-    //     }
-    //
-    //     Coordinate::new(-1, -1)
-    // }
+    pub fn starting_guard(&self) -> Guard {
+        Guard {
+            direction: self.starting_direction,
+            coordinate: self.starting_position,
+        }
+    }
 
     pub fn get_char_at(&self, coordinate: Coordinate) -> Option<&char> {
         if coordinate.y >= 0 && (coordinate.y as usize) < self.contents.len() &&
@@ -86,4 +98,8 @@ impl Grid {
             None
         }
     }
-}
\ No newline at end of file
+
+    pub fn is_obstacle(&self, character: char) -> bool {
+        self.obstacles.contains(&character)
+    }
+}
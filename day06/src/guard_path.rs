@@ -0,0 +1,125 @@
+use crate::guard_grid::{Coordinate, Direction, Grid, Guard};
+use crate::jump_table::JumpTable;
+use std::collections::HashMap;
+
+// One state of the walk, step counting the moves taken so far. Turning on the spot gives a
+// new state with the same step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathStep {
+    pub position: Coordinate,
+    pub direction: Direction,
+    pub step: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardPath {
+    pub steps: Vec<PathStep>,
+    // index into steps where the repeating part starts, None if the guard left the map
+    pub cycle_start: Option<usize>,
+}
+
+impl GuardPath {
+    pub fn loops(&self) -> bool {
+        self.cycle_start.is_some()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn cycle(&self) -> &[PathStep] {
+        match self.cycle_start {
+            Some(start) => &self.steps[start..],
+            None => &[],
+        }
+    }
+}
+
+// An obstacle that traps the guard, and the states the guard then repeats forever
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopWitness {
+    pub obstacle: Coordinate,
+    pub path: GuardPath,
+}
+
+// Every state of the guard in order until it leaves the map or repeats a state, with an
+// optional extra obstacle
+pub fn walk(grid: &Grid, start: &Guard, extra: Option<Coordinate>) -> GuardPath {
+    let mut guard = Guard {
+        direction: start.direction,
+        coordinate: start.coordinate,
+    };
+    let mut steps: Vec<PathStep> = Vec::new();
+    let mut seen: HashMap<(Coordinate, Direction), usize> = HashMap::new();
+    let mut step = 0;
+
+    loop {
+        if let Some(&first) = seen.get(&(guard.coordinate, guard.direction)) {
+            return GuardPath {
+                steps,
+                cycle_start: Some(first),
+            };
+        }
+        seen.insert((guard.coordinate, guard.direction), steps.len());
+        steps.push(PathStep {
+            position: guard.coordinate,
+            direction: guard.direction,
+            step,
+        });
+
+        let next = guard.coordinate + guard.get_movement();
+        match grid.get_char_at(next) {
+            None => {
+                return GuardPath {
+                    steps,
+                    cycle_start: None,
+                }
+            }
+            Some(&character) if grid.is_obstacle(character) => guard.rotate(),
+            Some(_) if Some(next) == extra => guard.rotate(),
+            Some(_) => {
+                guard.take_step();
+                step += 1;
+            }
+        }
+    }
+}
+
+// Each obstacle position that traps the guard, in the order the original path reaches it
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn loop_witnesses(grid: &Grid, start: &Guard) -> Vec<LoopWitness> {
+    JumpTable::new(grid)
+        .loop_obstacles(start)
+        .into_iter()
+        .map(|obstacle| LoopWitness {
+            obstacle,
+            path: walk(grid, start, Some(obstacle)),
+        })
+        .collect()
+}
+
+// Draws the steps over the map like the puzzle does: | for moving up or down, - for left or
+// right and + where both happen. Cells that are not '.' keep their character and the extra
+// obstacle is drawn as O
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn render(grid: &Grid, steps: &[PathStep], extra: Option<Coordinate>) -> Vec<String> {
+    let mut rows = grid.contents.clone();
+
+    for path_step in steps {
+        let (x, y) = (path_step.position.x as usize, path_step.position.y as usize);
+        let line = match path_step.direction {
+            Direction::UP | Direction::DOWN => '|',
+            Direction::LEFT | Direction::RIGHT => '-',
+        };
+
+        rows[y][x] = match (grid.contents[y][x], rows[y][x]) {
+            ('.', '.') => line,
+            ('.', drawn) if drawn != line => '+',
+            (_, drawn) => drawn,
+        };
+    }
+
+    if let Some(obstacle) = extra {
+        rows[obstacle.y as usize][obstacle.x as usize] = 'O';
+    }
+
+    rows.into_iter().map(|row| row.into_iter().collect()).collect()
+}
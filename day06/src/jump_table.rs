@@ -0,0 +1,212 @@
+use crate::guard_grid::{Coordinate, Direction, Grid, Guard};
+use common::parallel;
+
+const DIRECTIONS: [Direction; 4] = [Direction::UP, Direction::RIGHT, Direction::DOWN, Direction::LEFT];
+
+fn direction_index(direction: Direction) -> usize {
+    match direction {
+        Direction::UP => 0,
+        Direction::RIGHT => 1,
+        Direction::DOWN => 2,
+        Direction::LEFT => 3,
+    }
+}
+
+// Where a guard walking in a straight line ends up, the last cell it reaches in both cases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    // turns there because the next cell is an obstacle
+    Turn(Coordinate),
+    // walks off the map from there, past the edge or the end of a short row
+    Exit(Coordinate),
+}
+
+// For every cell and direction, where a guard walking that way stops. Lets a walk jump
+// straight from turn to turn. Rows may be shorter than the widest one, the missing cells are
+// off the map just like cells past the edge
+pub struct JumpTable {
+    width: usize,
+    height: usize,
+    row_lengths: Vec<usize>,
+    obstacles: Vec<bool>,
+    stops: Vec<[Stop; 4]>,
+}
+
+impl JumpTable {
+    pub fn new(grid: &Grid) -> Self {
+        let height = grid.contents.len();
+        let width = grid.contents.iter().map(|row| row.len()).max().unwrap_or(0);
+        let obstacles: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| grid.contents[y].get(x).is_some_and(|&character| grid.is_obstacle(character)))
+            .collect();
+
+        let mut table = JumpTable {
+            width,
+            height,
+            row_lengths: grid.contents.iter().map(|row| row.len()).collect(),
+            obstacles,
+            // only read for cells on the map, which the sweeps all fill in
+            stops: vec![[Stop::Exit(Coordinate::new(0, 0)); 4]; width * height],
+        };
+
+        // each line is swept against the direction of travel, remembering where the last
+        // obstacle or gap seen would make the guard stop
+        for x in 0..width {
+            table.sweep(Direction::UP, (0..height).map(|y| Coordinate::new(x as i32, y as i32)));
+            table.sweep(Direction::DOWN, (0..height).rev().map(|y| Coordinate::new(x as i32, y as i32)));
+        }
+        for y in 0..height {
+            table.sweep(Direction::LEFT, (0..width).map(|x| Coordinate::new(x as i32, y as i32)));
+            table.sweep(Direction::RIGHT, (0..width).rev().map(|x| Coordinate::new(x as i32, y as i32)));
+        }
+
+        table
+    }
+
+    fn sweep(&mut self, direction: Direction, cells: impl Iterator<Item = Coordinate>) {
+        let movement = Guard { direction, coordinate: Coordinate::new(0, 0) }.get_movement();
+        // None right after the edge or a gap, the next cell seen is the last one before the exit
+        let mut stop = None;
+
+        for cell in cells {
+            match self.index(cell) {
+                None => stop = None,
+                Some(index) if self.obstacles[index] => {
+                    stop = Some(Stop::Turn(Coordinate::new(cell.x - movement.x, cell.y - movement.y)));
+                }
+                Some(index) => {
+                    self.stops[index][direction_index(direction)] = *stop.get_or_insert(Stop::Exit(cell));
+                }
+            }
+        }
+    }
+
+    // None for anything off the map, including cells past the end of a short row
+    fn index(&self, coordinate: Coordinate) -> Option<usize> {
+        let inside = coordinate.x >= 0
+            && coordinate.y >= 0
+            && (coordinate.y as usize) < self.height
+            && (coordinate.x as usize) < self.row_lengths[coordinate.y as usize];
+
+        inside.then(|| coordinate.y as usize * self.width + coordinate.x as usize)
+    }
+
+    // where the guard stops when walking from the coordinate with one extra obstacle placed,
+    // None if it walks off the map
+    fn stop_with(&self, coordinate: Coordinate, direction: Direction, extra: Coordinate) -> Option<Coordinate> {
+        let stop = self.stops[self.index(coordinate)?][direction_index(direction)];
+        let movement = Guard { direction, coordinate }.get_movement();
+
+        // how many steps ahead a cell is, if it is straight ahead at all
+        let ahead = |cell: Coordinate| {
+            let (dx, dy) = (cell.x - coordinate.x, cell.y - coordinate.y);
+            let steps = dx * movement.x + dy * movement.y;
+            (steps > 0 && dx == steps * movement.x && dy == steps * movement.y).then_some(steps)
+        };
+
+        let (Stop::Turn(last) | Stop::Exit(last)) = stop;
+        let last_steps = ahead(last).unwrap_or(0);
+
+        // the extra obstacle only matters if the guard reaches it before stopping anyway
+        match (ahead(extra), stop) {
+            (Some(extra_steps), _) if extra_steps <= last_steps => {
+                Some(Coordinate::new(extra.x - movement.x, extra.y - movement.y))
+            }
+            (_, Stop::Turn(last)) => Some(last),
+            (_, Stop::Exit(_)) => None,
+        }
+    }
+
+    // Walks turn to turn from the guard with an extra obstacle, true if it never leaves the map.
+    // seen holds a stamp per cell and direction so one buffer can serve many walks
+    pub fn loops_with(&self, guard: &Guard, extra: Coordinate, seen: &mut [u32], stamp: u32) -> bool {
+        let mut coordinate = guard.coordinate;
+        let mut direction = guard.direction;
+
+        loop {
+            let Some(index) = self.index(coordinate) else {
+                return false;
+            };
+
+            let key = index * 4 + direction_index(direction);
+            if seen[key] == stamp {
+                return true;
+            }
+            seen[key] = stamp;
+
+            match self.stop_with(coordinate, direction, extra) {
+                Some(stop) => {
+                    coordinate = stop;
+                    direction = DIRECTIONS[(direction_index(direction) + 1) % 4];
+                }
+                None => return false,
+            }
+        }
+    }
+
+    // Every cell where one new obstacle traps the guard in a loop, in the order the original
+    // path reaches them. Each candidate is tried from the step just before the guard first
+    // enters it, since the path up to there is unchanged. The candidates are independent,
+    // so with the parallel feature they are tried across threads
+    pub fn loop_obstacles(&self, start: &Guard) -> Vec<Coordinate> {
+        let candidates = self.candidates(start);
+
+        let loops = parallel::map_init(
+            &candidates,
+            || (vec![0; self.width * self.height * 4], 0),
+            |(seen, walks), &(coordinate, direction, extra)| {
+                *walks += 1;
+                self.loops_with(&Guard { direction, coordinate }, extra, seen, *walks)
+            },
+        );
+
+        candidates
+            .iter()
+            .zip(loops)
+            .filter(|(_, loops)| *loops)
+            .map(|(&(_, _, extra), _)| extra)
+            .collect()
+    }
+
+    // each cell the original path enters for the first time, with where the guard stands and
+    // faces just before it
+    fn candidates(&self, start: &Guard) -> Vec<(Coordinate, Direction, Coordinate)> {
+        let mut tried = vec![false; self.width * self.height];
+        let mut walked = vec![false; self.width * self.height * 4];
+        let mut candidates = Vec::new();
+
+        let mut guard = Guard { direction: start.direction, coordinate: start.coordinate };
+        if let Some(index) = self.index(guard.coordinate) {
+            tried[index] = true;
+        }
+
+        while let Some(current) = self.index(guard.coordinate) {
+            // the original path already loops, so it never reaches anything new
+            let key = current * 4 + direction_index(guard.direction);
+            if walked[key] {
+                break;
+            }
+            walked[key] = true;
+
+            let next = guard.coordinate + guard.get_movement();
+            let Some(index) = self.index(next) else {
+                break;
+            };
+
+            if self.obstacles[index] {
+                guard.rotate();
+                continue;
+            }
+
+            if !tried[index] {
+                tried[index] = true;
+                candidates.push((guard.coordinate, guard.direction, next));
+            }
+
+            guard.take_step();
+        }
+
+        candidates
+    }
+}
@@ -1,20 +1,48 @@
-use crate::guard_grid::Direction::UP;
-use crate::guard_grid::{Coordinate, Direction, Grid, Guard};
+use crate::guard_grid::{Coordinate, Direction, Grid};
 use common::parse_to_array;
+use crate::guard_path::walk;
+use crate::jump_table::JumpTable;
 use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 
 mod guard_grid;
+mod guard_path;
+mod jump_table;
 
-fn parse_input(input: &Vec<String>) -> Grid {
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GridError {
+    NoGuard,
+    MultipleGuards { positions: Vec<Coordinate> },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::NoGuard => write!(f, "no guard (^, >, v or <) on the map"),
+            GridError::MultipleGuards { positions } => {
+                write!(f, "{} guards on the map, expected one", positions.len())
+            }
+        }
+    }
+}
+
+impl Error for GridError {}
+
+fn parse_input(input: &[String]) -> Result<Grid, GridError> {
+    parse_input_with_obstacles(input, &['#'])
+}
+
+fn parse_input_with_obstacles(input: &[String], obstacles: &[char]) -> Result<Grid, GridError> {
     let mut contents: Vec<Vec<char>> = Vec::new();
-    let mut start = (0, 0);
+    let mut guards: Vec<(Coordinate, Direction)> = Vec::new();
 
     for (line_index, line) in input.iter().enumerate() {
         let mut chars: Vec<char> = Vec::new();
 
         for (char_index, character) in line.chars().enumerate() {
-            if character == '^'{
-                start = (char_index as i32, line_index as i32)
+            if let Some(direction) = Direction::from_glyph(character) {
+                guards.push((Coordinate::new(char_index as i32, line_index as i32), direction));
             }
             chars.push(character);
         }
@@ -22,93 +50,39 @@ fn parse_input(input: &Vec<String>) -> Grid {
         contents.push(chars);
     }
 
-    Grid {
+    let (starting_position, starting_direction) = match guards.as_slice() {
+        [] => return Err(GridError::NoGuard),
+        [guard] => *guard,
+        _ => {
+            return Err(GridError::MultipleGuards {
+                positions: guards.iter().map(|&(position, _)| position).collect(),
+            })
+        }
+    };
+
+    Ok(Grid {
         contents,
-        starting_position: Coordinate::new(start.0, start.1)
-    }
+        starting_position,
+        starting_direction,
+        obstacles: obstacles.to_vec(),
+    })
 }
 
 //returns unique guard steps as well as whether a loop was detected
 fn unique_guard_steps(grid: &Grid) -> (HashSet<Coordinate>, bool) {
-    let mut contains_loop = false;
-
-    let mut guard: Guard = Guard {
-        direction: UP,
-        coordinate: grid.starting_position,
-    };
-
-    let mut visited: HashSet<(Coordinate, Direction)> = HashSet::new();
-    visited.insert((guard.coordinate, guard.direction));
-
-    let mut unique_coords: HashSet<Coordinate> = HashSet::new();
-    unique_coords.insert(guard.coordinate);
-
-    loop {
-        let next = guard.coordinate + guard.get_movement();
+    let path = walk(grid, &grid.starting_guard(), None);
 
-        if let Some(char) = grid.get_char_at(next) {
-            match char {
-                '.' | '^' => {
-                    guard.take_step();
-                    if !visited.insert((guard.coordinate, guard.direction)) {
-                        contains_loop = true;
-                        break;
-                    }
-                    unique_coords.insert(guard.coordinate);
-                }
-                '#' => guard.rotate(),
-                _ => break,
-            }
-        } else {
-            break;
-        }
-    }
-
-    (unique_coords, contains_loop)
+    (path.steps.iter().map(|step| step.position).collect(), path.loops())
 }
 
-//permute grid based on original path
-fn generate_all_possible_grids(grid: &Grid) -> Vec<Grid> {
-    let mut grids = Vec::new();
-    let placement_coords = unique_guard_steps(grid).0;
-
-    for coordinate in placement_coords.iter() {
-        let char = grid.get_char_at(*coordinate).unwrap();
-        if *char == '.' {
-            let mut permute_contents = grid.contents.clone();
-
-            permute_contents[coordinate.y as usize][coordinate.x as usize] = '#';
-
-            grids.push(
-                Grid {
-                    contents: permute_contents,
-                    starting_position: grid.starting_position,
-                }
-            );
-        }
-    }
-
-    grids
-}
-
-// check all permutations, any with loops are counted
+// check every cell on the original path, any obstacle causing a loop is counted
 fn get_total_possible_loops(grid: &Grid) -> usize {
-    let mut count = 0;
-
-    let grid_permutation = generate_all_possible_grids(grid);
-
-    for grid in grid_permutation {
-        if unique_guard_steps(&grid).1 {
-            count += 1;
-        }
-    }
-
-    count
+    JumpTable::new(grid).loop_obstacles(&grid.starting_guard()).len()
 }
 
 fn main() {
     let input = parse_to_array("day06part01.txt").unwrap();
-    let grid = parse_input(&input);
+    let grid = parse_input(&input).unwrap();
 
     let part_one = unique_guard_steps(&grid);
     println!("{}", part_one.0.len());
@@ -120,6 +94,10 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::guard_grid::Direction::UP;
+    use crate::guard_grid::Guard;
+    use crate::guard_path::{loop_witnesses, render, PathStep};
+    use common::random::SeededRng;
 
     #[test]
     fn part_one_test() {
@@ -136,7 +114,7 @@ mod tests {
             "......#...".to_string(),
         ];
 
-        let grid = parse_input(&input);
+        let grid = parse_input(&input).unwrap();
 
         let result = unique_guard_steps(&grid);
 
@@ -158,10 +136,222 @@ mod tests {
             "......#...".to_string(),
         ];
 
-        let grid = parse_input(&input);
+        let grid = parse_input(&input).unwrap();
 
         let result = get_total_possible_loops(&grid);
 
         assert_eq!(result, 6)
     }
+
+    // the old approach, a fresh walk on a copy of the grid for every candidate
+    fn brute_force_loops(grid: &Grid) -> usize {
+        let mut count = 0;
+
+        for coordinate in unique_guard_steps(grid).0 {
+            if grid.get_char_at(coordinate) == Some(&'.') {
+                let mut contents = grid.contents.clone();
+                contents[coordinate.y as usize][coordinate.x as usize] = '#';
+                let permuted = Grid {
+                    contents,
+                    starting_position: grid.starting_position,
+                    starting_direction: grid.starting_direction,
+                    obstacles: grid.obstacles.clone(),
+                };
+
+                if unique_guard_steps(&permuted).1 {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    // with ragged set, rows are cut short at random but never before the guard in the middle
+    fn generated_grid(size: usize, seed: u64, ragged: bool) -> Vec<String> {
+        let mut rng = SeededRng::new(seed);
+        let mut rows: Vec<Vec<char>> = (0..size)
+            .map(|_| (0..size).map(|_| if rng.below(9) == 0 { '#' } else { '.' }).collect())
+            .collect();
+        rows[size / 2][size / 2] = '^';
+
+        if ragged {
+            for (y, row) in rows.iter_mut().enumerate() {
+                let shortest = if y == size / 2 { size / 2 + 1 } else { 0 };
+                row.truncate(shortest + rng.below((size - shortest) as u64 + 1) as usize);
+            }
+        }
+
+        rows.into_iter().map(|row| row.into_iter().collect()).collect()
+    }
+
+    #[test]
+    fn jump_table_matches_brute_force_test() {
+        for seed in 0..20 {
+            for ragged in [false, true] {
+                let grid = parse_input(&generated_grid(25, seed, ragged)).unwrap();
+                let expected = brute_force_loops(&grid);
+                assert_eq!(get_total_possible_loops(&grid), expected, "seed {}, ragged {}", seed, ragged);
+            }
+        }
+
+        // cells past the end of a short row are off the map, not open floor
+        let input = ["..#...", ".....#", "..^", "#....", "....#."];
+        let grid = parse_input(&input.map(|line| line.to_string())).unwrap();
+        assert!(!unique_guard_steps(&grid).1);
+        assert!(JumpTable::new(&grid).loop_obstacles(&grid.starting_guard()).is_empty());
+        assert_eq!(brute_force_loops(&grid), 0);
+    }
+
+    #[test]
+    fn loop_obstacles_test() {
+        let grid = parse_input(&example()).unwrap();
+        let start = grid.starting_guard();
+
+        let mut found = JumpTable::new(&grid).loop_obstacles(&start);
+        found.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+        assert_eq!(
+            found,
+            vec![
+                Coordinate::new(3, 6),
+                Coordinate::new(6, 7),
+                Coordinate::new(7, 7),
+                Coordinate::new(1, 8),
+                Coordinate::new(3, 8),
+                Coordinate::new(7, 9),
+            ]
+        );
+
+        // boxed in, the guard only turns and the walk ends
+        let grid = parse_input(&[".#.".to_string(), "#^#".to_string(), ".#.".to_string()]).unwrap();
+        assert!(unique_guard_steps(&grid).1);
+        assert_eq!(get_total_possible_loops(&grid), 0);
+    }
+
+    fn example() -> Vec<String> {
+        vec![
+            "....#.....".to_string(),
+            ".........#".to_string(),
+            "..........".to_string(),
+            "..#.......".to_string(),
+            ".......#..".to_string(),
+            "..........".to_string(),
+            ".#..^.....".to_string(),
+            "........#.".to_string(),
+            "#.........".to_string(),
+            "......#...".to_string(),
+        ]
+    }
+
+    #[test]
+    fn ordered_path_test() {
+        let grid = parse_input(&example()).unwrap();
+        let start = grid.starting_guard();
+        let path = walk(&grid, &start, None);
+
+        assert!(!path.loops());
+        assert!(path.cycle().is_empty());
+        assert_eq!(
+            path.steps[4..7],
+            [
+                PathStep { position: Coordinate::new(4, 2), direction: UP, step: 4 },
+                PathStep { position: Coordinate::new(4, 1), direction: UP, step: 5 },
+                PathStep { position: Coordinate::new(4, 1), direction: Direction::RIGHT, step: 5 },
+            ]
+        );
+        assert_eq!(
+            path.steps.last(),
+            Some(&PathStep { position: Coordinate::new(7, 9), direction: Direction::DOWN, step: 44 })
+        );
+        assert_eq!(unique_guard_steps(&grid).0.len(), 41);
+    }
+
+    #[test]
+    fn loop_witness_test() {
+        let grid = parse_input(&example()).unwrap();
+        let start = grid.starting_guard();
+        let witnesses = loop_witnesses(&grid, &start);
+
+        assert_eq!(witnesses.len(), 6);
+        for witness in &witnesses {
+            assert!(witness.path.loops());
+
+            // the guard is back where the cycle started, facing the same way
+            let cycle = witness.path.cycle();
+            let last = cycle.last().unwrap();
+            let mut guard = Guard { direction: last.direction, coordinate: last.position };
+            let next = guard.coordinate + guard.get_movement();
+            if grid.get_char_at(next) == Some(&'#') || next == witness.obstacle {
+                guard.rotate();
+            } else {
+                guard.take_step();
+            }
+            assert_eq!((guard.coordinate, guard.direction), (cycle[0].position, cycle[0].direction));
+        }
+
+        let first = &witnesses[0];
+        assert_eq!(first.obstacle, Coordinate::new(3, 6));
+        assert_eq!(
+            render(&grid, &first.path.steps, Some(first.obstacle)),
+            vec![
+                "....#.....",
+                "....+---+#",
+                "....|...|.",
+                "..#.|...|.",
+                "....|..#|.",
+                "....|...|.",
+                ".#.O^---+.",
+                "........#.",
+                "#.........",
+                "......#...",
+            ]
+        );
+    }
+
+    #[test]
+    fn guard_glyph_test() {
+        let rotated = |glyph: char| {
+            example()
+                .into_iter()
+                .map(|line| line.replace('^', &glyph.to_string()))
+                .collect::<Vec<String>>()
+        };
+
+        for (glyph, direction) in [('^', UP), ('>', Direction::RIGHT), ('v', Direction::DOWN), ('<', Direction::LEFT)] {
+            let grid = parse_input(&rotated(glyph)).unwrap();
+            assert_eq!(grid.starting_position, Coordinate::new(4, 6));
+            assert_eq!(grid.starting_direction, direction);
+        }
+
+        // facing right the guard goes straight to the edge without turning
+        let grid = parse_input(&rotated('>')).unwrap();
+        assert_eq!(unique_guard_steps(&grid), ((4..10).map(|x| Coordinate::new(x, 6)).collect(), false));
+
+        // facing down nothing is below it either
+        let grid = parse_input(&rotated('v')).unwrap();
+        assert_eq!(unique_guard_steps(&grid).0.len(), 4);
+    }
+
+    #[test]
+    fn guard_errors_test() {
+        assert_eq!(parse_input(&["....".to_string(), ".#..".to_string()]).err(), Some(GridError::NoGuard));
+        assert_eq!(
+            parse_input(&["^..<".to_string(), ".#..".to_string()]).err(),
+            Some(GridError::MultipleGuards { positions: vec![Coordinate::new(0, 0), Coordinate::new(3, 0)] })
+        );
+        assert_eq!(GridError::NoGuard.to_string(), "no guard (^, >, v or <) on the map");
+    }
+
+    #[test]
+    fn configurable_obstacles_test() {
+        let input: Vec<String> = example().into_iter().map(|line| line.replace('#', "O")).collect();
+
+        // with the default obstacles the O cells are open floor
+        let grid = parse_input(&input).unwrap();
+        assert_eq!(unique_guard_steps(&grid).0.len(), 7);
+
+        let grid = parse_input_with_obstacles(&input, &['O', '#']).unwrap();
+        assert_eq!(unique_guard_steps(&grid).0.len(), 41);
+        assert_eq!(get_total_possible_loops(&grid), 6);
+    }
 }
@@ -1,93 +1,205 @@
-use common::parse_to_array;
+use crate::operators::{expression, Add, Concat, Multiply, Operator};
+use crate::solver::{all_operators, count_operators, find_operators, find_operators_backward};
+use common::big::BigUint;
+use common::{parallel, parse_to_array};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 
-fn parse_calibration_equations(input: &Vec<String>) -> Vec<Vec<i64>> {
-    let mut output: Vec<Vec<i64>> = Vec::new();
+mod operators;
+mod solver;
 
-    for line in input {
-        let mut calibration_equation = Vec::new();
-        let slices = line.split(" ");
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CalibrationError {
+    MissingColon { line: usize },
+    InvalidNumber { line: usize, text: String },
+    MissingValues { line: usize },
+    SumOverflow,
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::MissingColon { line } => write!(f, "line {}: expected \"target: values\"", line),
+            CalibrationError::InvalidNumber { line, text } => write!(f, "line {}: {:?} is not a valid number", line, text),
+            CalibrationError::MissingValues { line } => write!(f, "line {}: no values after the target", line),
+            CalibrationError::SumOverflow => write!(f, "total calibration result does not fit in a u128"),
+        }
+    }
+}
+
+impl Error for CalibrationError {}
+
+// Each equation is its target followed by its values. Line numbers in errors start at 1
+fn parse_equations<N: FromStr>(input: &[String]) -> Result<Vec<Vec<N>>, CalibrationError> {
+    let mut output: Vec<Vec<N>> = Vec::new();
+
+    for (index, line) in input.iter().enumerate() {
+        let line_number = index + 1;
+        let (target, values) = line
+            .split_once(':')
+            .ok_or(CalibrationError::MissingColon { line: line_number })?;
 
-        for slice in slices {
-            let number: i64 = slice.trim_end_matches(':').parse().unwrap();
+        let mut calibration_equation = Vec::new();
+        for slice in std::iter::once(target.trim()).chain(values.split_whitespace()) {
+            let number: N = slice.parse().map_err(|_| CalibrationError::InvalidNumber {
+                line: line_number,
+                text: slice.to_string(),
+            })?;
             calibration_equation.push(number);
         }
 
+        if calibration_equation.len() < 2 {
+            return Err(CalibrationError::MissingValues { line: line_number });
+        }
+
         output.push(calibration_equation);
     }
 
-    output
+    Ok(output)
 }
 
-fn concat(first: i64, second: i64) -> i64 {
-    let first_str = first.to_string();
-    let second_str = second.to_string();
+fn parse_calibration_equations(input: &[String]) -> Result<Vec<Vec<u128>>, CalibrationError> {
+    parse_equations(input)
+}
 
-    let result_str = format!("{}{}", first_str, second_str);
+fn calibration_operators(with_concat: bool) -> &'static [&'static dyn Operator<u128>] {
+    if with_concat {
+        &[&Multiply, &Add, &Concat]
+    } else {
+        &[&Multiply, &Add]
+    }
+}
+
+// working back from the target only ever shrinks it, and searching forwards ends any branch
+// that overflows, so nothing here can overflow
+fn has_operator_match(calibration_equation: &[u128], with_concat: bool) -> bool {
+    let operators = calibration_operators(with_concat);
 
-    result_str.parse::<i64>().unwrap()
+    find_operators_backward(calibration_equation[0], &calibration_equation[1..], operators).is_some()
 }
 
-fn has_operator_match(calibration_equation: &Vec<i64>, with_concat: bool) -> bool {
-    let equal_to = &calibration_equation[0];
+// how many operator sequences make the equation true, 0 when has_operator_match is false
+fn count_operator_matches(calibration_equation: &[u128], with_concat: bool) -> u128 {
+    let operators = calibration_operators(with_concat);
+
+    count_operators(calibration_equation[0], &calibration_equation[1..], operators)
+}
+
+// the first limit expressions making the equation true, e.g. 10 * 19
+#[cfg_attr(not(test), allow(dead_code))]
+fn list_operator_matches(calibration_equation: &[u128], with_concat: bool, limit: usize) -> Vec<String> {
+    let operators = calibration_operators(with_concat);
     let values = &calibration_equation[1..];
 
-    let mut stack: Vec<(usize, i64)> = Vec::new();
-    stack.push((0, 0));
+    all_operators(calibration_equation[0], values, operators, limit)
+        .iter()
+        .map(|found| expression(values, found))
+        .collect()
+}
 
-    while let Some((index, value)) = stack.pop() {
-        if value == *equal_to {
-            return true;
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EquationAmbiguity {
+    target: u128,
+    without_concat: u128,
+    with_concat: u128,
+}
 
-        if index < values.len() {
-            let next = values[index];
-            stack.push((index + 1, value * next));
-            stack.push((index + 1, value + next));
-            if with_concat {stack.push((index + 1, concat(value, next)))};
-        }
+impl EquationAmbiguity {
+    // true for the equations only part two counts
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn needs_concat(&self) -> bool {
+        self.without_concat == 0 && self.with_concat > 0
     }
+}
 
-    false
+#[cfg_attr(not(test), allow(dead_code))]
+fn equation_ambiguity(calibration_equations: &[Vec<u128>]) -> Vec<EquationAmbiguity> {
+    calibration_equations
+        .iter()
+        .map(|calibration_equation| EquationAmbiguity {
+            target: calibration_equation[0],
+            without_concat: count_operator_matches(calibration_equation, false),
+            with_concat: count_operator_matches(calibration_equation, true),
+        })
+        .collect()
 }
 
-fn find_total_calibration_sum(calibration_equations: &Vec<Vec<i64>>) -> i64 {
-    let mut result = 0;
+// The values with the operators that reach the target written between them, e.g. 81 + 40 * 27
+#[cfg_attr(not(test), allow(dead_code))]
+fn reconstruct_equation<N>(calibration_equation: &[N], operators: &[&dyn Operator<N>]) -> Option<String>
+where
+    N: Clone + PartialOrd + From<u8> + fmt::Display,
+{
+    let values = &calibration_equation[1..];
+    let found = find_operators(calibration_equation[0].clone(), values, operators)?;
+
+    Some(expression(values, &found))
+}
 
-    for calibration_equation in calibration_equations {
-        if has_operator_match(calibration_equation, false) {
-            result += calibration_equation[0];
+// any one target fits in a u128, but the sum of several may not
+fn sum_matching_targets(calibration_equations: &[Vec<u128>], with_concat: bool) -> Result<u128, CalibrationError> {
+    let matches = parallel::map(calibration_equations, |calibration_equation| {
+        has_operator_match(calibration_equation, with_concat)
+    });
+
+    let mut result: u128 = 0;
+
+    for (calibration_equation, matched) in calibration_equations.iter().zip(matches) {
+        if matched {
+            result = result
+                .checked_add(calibration_equation[0])
+                .ok_or(CalibrationError::SumOverflow)?;
         }
     }
 
-    result
+    Ok(result)
+}
+
+fn find_total_calibration_sum(calibration_equations: &[Vec<u128>]) -> Result<u128, CalibrationError> {
+    sum_matching_targets(calibration_equations, false)
 }
 
-fn find_total_calibration_sum_with_concatenation(calibration_equations: &Vec<Vec<i64>>) -> i64 {
-    let mut result = 0;
+fn find_total_calibration_sum_with_concatenation(calibration_equations: &[Vec<u128>]) -> Result<u128, CalibrationError> {
+    sum_matching_targets(calibration_equations, true)
+}
+
+// For targets too big for u128. Searches forwards, dropping partial results past the target
+#[cfg_attr(not(test), allow(dead_code))]
+fn find_total_calibration_sum_big(input: &[String], with_concat: bool) -> Result<BigUint, CalibrationError> {
+    let operators: &[&dyn Operator<BigUint>] = if with_concat {
+        &[&Multiply, &Add, &Concat]
+    } else {
+        &[&Multiply, &Add]
+    };
+    let mut result = BigUint::default();
 
-    for calibration_equation in calibration_equations {
-        if has_operator_match(calibration_equation, true) {
-            result += calibration_equation[0];
+    for calibration_equation in parse_equations::<BigUint>(input)? {
+        let (target, values) = calibration_equation.split_first().unwrap();
+        if find_operators(target.clone(), values, operators).is_some() {
+            result = result.add(target);
         }
     }
 
-    result
+    Ok(result)
 }
 
 fn main() {
     let input = parse_to_array("day07part01.txt").unwrap();
-    let calibration_equations = parse_calibration_equations(&input);
+    let calibration_equations = parse_calibration_equations(&input).unwrap();
 
-    let part_one = find_total_calibration_sum(&calibration_equations);
+    let part_one = find_total_calibration_sum(&calibration_equations).unwrap();
     println!("{}", part_one);
 
-    let part_two = find_total_calibration_sum_with_concatenation(&calibration_equations);
+    let part_two = find_total_calibration_sum_with_concatenation(&calibration_equations).unwrap();
     println!("{}", part_two);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::operators::{Divide, Subtract};
+    use common::random::SeededRng;
 
     #[test]
     fn part_one_test() {
@@ -103,9 +215,9 @@ mod tests {
             "292: 11 6 16 20".to_string(),
         ];
 
-        let calibration_equations = parse_calibration_equations(&input);
+        let calibration_equations = parse_calibration_equations(&input).unwrap();
 
-        assert_eq!(find_total_calibration_sum(&calibration_equations), 3749);
+        assert_eq!(find_total_calibration_sum(&calibration_equations), Ok(3749));
     }
 
     #[test]
@@ -122,8 +234,325 @@ mod tests {
             "292: 11 6 16 20".to_string(),
         ];
 
-        let calibration_equations = parse_calibration_equations(&input);
+        let calibration_equations = parse_calibration_equations(&input).unwrap();
+
+        assert_eq!(find_total_calibration_sum_with_concatenation(&calibration_equations), Ok(11387));
+    }
+
+    #[test]
+    fn reconstruct_equation_test() {
+        let operators: &[&dyn Operator] = &[&Add, &Multiply, &Concat];
+
+        assert_eq!(reconstruct_equation(&[190, 10, 19], operators), Some("10 * 19".to_string()));
+        assert_eq!(reconstruct_equation(&[3267, 81, 40, 27], operators), Some("81 + 40 * 27".to_string()));
+        assert_eq!(reconstruct_equation(&[156, 15, 6], operators), Some("15 || 6".to_string()));
+        assert_eq!(
+            reconstruct_equation(&[7290, 6, 8, 6, 15], operators),
+            Some("6 * 8 || 6 * 15".to_string())
+        );
+        assert_eq!(reconstruct_equation(&[83, 17, 5], operators), None);
+
+        // every value has to be used, stopping early at 10 does not count
+        assert_eq!(reconstruct_equation(&[10_i64, 10, 5], &[&Add, &Multiply]), None);
+    }
+
+    #[test]
+    fn custom_operators_test() {
+        assert_eq!(
+            reconstruct_equation(&[6, 20, 4, 1], &[&Add, &Subtract, &Divide]),
+            Some("20 / 4 + 1".to_string())
+        );
+        assert_eq!(reconstruct_equation(&[-9, 1, 10], &[&Add, &Subtract]), Some("1 - 10".to_string()));
+        assert_eq!(reconstruct_equation(&[3, 7, 2], &[&Divide]), Some("7 / 2".to_string()));
+        assert_eq!(reconstruct_equation(&[1, 7, 0], &[&Divide]), None);
+
+        assert_eq!(Add.inverse(3267_i64, 27), Some(3240));
+        assert_eq!(Multiply.inverse(3267_i64, 27), Some(121));
+        assert_eq!(Multiply.inverse(3267_i64, 10), None);
+        assert_eq!(Subtract.inverse(-9, 10), Some(1));
+        assert_eq!(Divide.inverse(3, 2), None);
+    }
+
+    // small values joined by random operators, targets that overflow are skipped
+    fn generated_equations(count: usize, length: usize, seed: u64) -> Vec<Vec<i64>> {
+        let operators: [&dyn Operator; 3] = [&Add, &Multiply, &Concat];
+        let mut rng = SeededRng::new(seed);
+
+        let mut equations = Vec::new();
+        while equations.len() < count {
+            let values: Vec<i64> = (0..length).map(|_| rng.below(20) as i64 + 1).collect();
+            let target = values[1..].iter().try_fold(values[0], |total, &value| {
+                operators[rng.below(3) as usize].apply(total, value)
+            });
+
+            if let Some(target) = target {
+                // every other equation gets a nearby target that is usually unreachable
+                let target = if equations.len() % 2 == 0 { target } else { target + 1 };
+                equations.push([vec![target], values].concat());
+            }
+        }
+
+        equations
+    }
+
+    #[test]
+    fn backward_solver_matches_forward_test() {
+        let operator_sets: [&[&dyn Operator]; 2] = [&[&Multiply, &Add], &[&Multiply, &Add, &Concat]];
+        let mut equations = parse_equations::<i64>(&[
+            "190: 10 19".to_string(),
+            "3267: 81 40 27".to_string(),
+            "83: 17 5".to_string(),
+            "156: 15 6".to_string(),
+            "7290: 6 8 6 15".to_string(),
+            "161011: 16 10 13".to_string(),
+            "192: 17 8 14".to_string(),
+            "21037: 9 7 18 13".to_string(),
+            "292: 11 6 16 20".to_string(),
+        ])
+        .unwrap();
+        equations.extend(generated_equations(200, 6, 3));
+        equations.push(vec![10, 10, 5]);
+        equations.push(vec![100, 10, 0]);
+
+        // multiplying by zero cannot be undone, these only match through it
+        equations.push(vec![5, 3, 0, 5]);
+        equations.push(vec![0, 5, 0]);
+        equations.push(vec![7, 9, 4, 0, 7]);
+        equations.push(vec![0, 0, 0]);
+
+        for operators in operator_sets {
+            for equation in &equations {
+                let forward = find_operators(equation[0], &equation[1..], operators);
+                let backward = find_operators_backward(equation[0], &equation[1..], operators);
+                assert_eq!(forward.is_some(), backward.is_some(), "{:?}", equation);
+
+                if let Some(found) = backward {
+                    let value = equation[2..].iter().zip(&found).try_fold(equation[1], |total, (&value, operator)| {
+                        operator.apply(total, value)
+                    });
+                    assert_eq!(value, Some(equation[0]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_equation_test() {
+        // 3^29 operator sequences, far too many to search forwards
+        for equation in generated_equations(20, 30, 17) {
+            let operators: &[&dyn Operator] = &[&Multiply, &Add, &Concat];
+            let found = find_operators_backward(equation[0], &equation[1..], operators);
+
+            if let Some(found) = found {
+                assert_eq!(found.len(), 29);
+            }
+        }
+
+        let mut equation = vec![1; 41];
+        equation[0] = 40;
+        assert!(has_operator_match(&equation, true));
+
+        // all ones and out of reach, each of the 2^40 sequences ends somewhere that already failed
+        let mut equation = vec![1; 41];
+        equation[0] = 100;
+        assert!(!has_operator_match(&equation, false));
+        equation[0] = 40;
+        assert!(has_operator_match(&equation, false));
+
+        assert!(has_operator_match(&[5, 3, 0, 5], false));
+        assert!(has_operator_match(&[0, 5, 0], false));
+        assert!(!has_operator_match(&[6, 3, 0, 5], true));
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        let parse = |lines: &[&str]| {
+            parse_calibration_equations(&lines.iter().map(|line| line.to_string()).collect::<Vec<String>>())
+        };
+
+        assert_eq!(parse(&["190: 10 19", "3267 81 40 27"]), Err(CalibrationError::MissingColon { line: 2 }));
+        assert_eq!(
+            parse(&["190: 10 x19"]),
+            Err(CalibrationError::InvalidNumber { line: 1, text: "x19".to_string() })
+        );
+        assert_eq!(
+            parse(&["-190: 10 19"]),
+            Err(CalibrationError::InvalidNumber { line: 1, text: "-190".to_string() })
+        );
+        assert_eq!(
+            parse(&["340282366920938463463374607431768211456: 1 1"]),
+            Err(CalibrationError::InvalidNumber {
+                line: 1,
+                text: "340282366920938463463374607431768211456".to_string()
+            })
+        );
+        assert_eq!(parse(&["190:"]), Err(CalibrationError::MissingValues { line: 1 }));
+        assert_eq!(parse(&["190:  10   19 "]), Ok(vec![vec![190, 10, 19]]));
+        assert_eq!(
+            CalibrationError::MissingColon { line: 2 }.to_string(),
+            "line 2: expected \"target: values\""
+        );
+    }
+
+    #[test]
+    fn wide_targets_test() {
+        // every partial result past u64, and some past i64 and u128 on the way
+        let input = vec![
+            "340282366920938463463374607431768211455: 3402823669 20938463463 37460743176 8211455".to_string(),
+            "18446744073709551616: 4294967296 4294967296".to_string(),
+            "18446744073709551617: 4294967296 4294967296".to_string(),
+            "99999999999999999999999999999999999999: 99999999999999999999 9999999999999999999 2".to_string(),
+        ];
+        let equations = parse_calibration_equations(&input).unwrap();
+
+        assert!(has_operator_match(&equations[0], true));
+        assert!(!has_operator_match(&equations[0], false));
+        assert_eq!(find_total_calibration_sum_with_concatenation(&equations[..1]), Ok(u128::MAX));
+        assert!(has_operator_match(&equations[1], false));
+        assert!(!has_operator_match(&equations[2], true));
+        assert!(!has_operator_match(&equations[3], true));
+
+        let operators: &[&dyn Operator<u128>] = &[&Add, &Multiply, &Concat];
+        assert_eq!(
+            reconstruct_equation(&equations[0], operators),
+            Some("3402823669 || 20938463463 || 37460743176 || 8211455".to_string())
+        );
+        assert_eq!(reconstruct_equation(&equations[3], operators), None);
+    }
+
+    #[test]
+    fn sum_overflow_test() {
+        let equations = parse_calibration_equations(&[
+            "340282366920938463463374607431768211455: 340282366920938463463374607431768211455".to_string(),
+            "1: 1".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(find_total_calibration_sum(&equations), Err(CalibrationError::SumOverflow));
+        assert_eq!(find_total_calibration_sum_with_concatenation(&equations), Err(CalibrationError::SumOverflow));
+        assert_eq!(find_total_calibration_sum(&equations[..1]), Ok(u128::MAX));
+
+        // the big-integer mode has no limit
+        let input = vec![
+            "340282366920938463463374607431768211455: 340282366920938463463374607431768211455".to_string(),
+            "1: 1".to_string(),
+        ];
+        assert_eq!(
+            find_total_calibration_sum_big(&input, false).unwrap().to_string(),
+            "340282366920938463463374607431768211456"
+        );
+    }
+
+    #[test]
+    fn pruning_test() {
+        // products overflow i64 long before the last value, those branches end instead of panicking
+        let mut equation = vec![1_000_000_007_i64];
+        equation.extend([1_000_000, 1_000_000, 1_000_000, 1_000_000, 7]);
+        let operators: &[&dyn Operator] = &[&Multiply, &Concat, &Add];
+        assert_eq!(reconstruct_equation(&equation, operators), None);
+
+        let equation = [2_000_007, 1_000_000, 1_000_000, 7];
+        assert_eq!(reconstruct_equation(&equation, operators), Some("1000000 + 1000000 + 7".to_string()));
+
+        // a zero operand can bring a result back down, so pruning has to stay off
+        assert_eq!(reconstruct_equation(&[5, 100, 0, 5], operators), Some("100 * 0 || 5".to_string()));
+    }
+
+    #[test]
+    fn big_calibration_sum_test() {
+        let mut input = vec![
+            "190: 10 19".to_string(),
+            "3267: 81 40 27".to_string(),
+            "83: 17 5".to_string(),
+            "156: 15 6".to_string(),
+            "7290: 6 8 6 15".to_string(),
+            "161011: 16 10 13".to_string(),
+            "192: 17 8 14".to_string(),
+            "21037: 9 7 18 13".to_string(),
+            "292: 11 6 16 20".to_string(),
+        ];
+        assert_eq!(find_total_calibration_sum_big(&input, false).unwrap().to_string(), "3749");
+        assert_eq!(find_total_calibration_sum_big(&input, true).unwrap().to_string(), "11387");
+
+        // 40 digit target, past u128
+        input.push("1234567890123456789012345678901234567890: 12345678901234567890 12345678901234567890".to_string());
+        input.push("1234567890123456789012345678901234567891: 12345678901234567890 12345678901234567890".to_string());
+        assert_eq!(
+            find_total_calibration_sum_big(&input, true).unwrap().to_string(),
+            "1234567890123456789012345678901234579277"
+        );
+        assert_eq!(find_total_calibration_sum_big(&input, false).unwrap().to_string(), "3749");
+        assert!(parse_calibration_equations(&input).is_err());
+    }
+
+    #[test]
+    fn count_operator_matches_test() {
+        let input = vec![
+            "190: 10 19".to_string(),
+            "3267: 81 40 27".to_string(),
+            "83: 17 5".to_string(),
+            "156: 15 6".to_string(),
+            "7290: 6 8 6 15".to_string(),
+            "161011: 16 10 13".to_string(),
+            "192: 17 8 14".to_string(),
+            "21037: 9 7 18 13".to_string(),
+            "292: 11 6 16 20".to_string(),
+            "4: 2 2".to_string(),
+            "44: 2 2 2 2".to_string(),
+            "5: 3 0 5".to_string(),
+            "0: 5 0 0".to_string(),
+        ];
+        let equations = parse_calibration_equations(&input).unwrap();
+        let report = equation_ambiguity(&equations);
+
+        let counts: Vec<(u128, u128)> = report.iter().map(|line| (line.without_concat, line.with_concat)).collect();
+        assert_eq!(
+            counts,
+            vec![(1, 1), (2, 2), (0, 0), (0, 1), (0, 1), (0, 0), (0, 1), (0, 0), (1, 1), (2, 2), (0, 2), (1, 2), (3, 5)]
+        );
+
+        let needs_concat: Vec<u128> = report.iter().filter(|line| line.needs_concat()).map(|line| line.target).collect();
+        assert_eq!(needs_concat, vec![156, 7290, 192, 44]);
+
+        for equation in &equations {
+            for with_concat in [false, true] {
+                let count = count_operator_matches(equation, with_concat);
+                assert_eq!(count > 0, has_operator_match(equation, with_concat));
+                assert_eq!(count as usize, list_operator_matches(equation, with_concat, usize::MAX).len());
+            }
+        }
+    }
+
+    #[test]
+    fn count_without_listing_test() {
+        // with ones, * keeps the total and + adds one, so reaching 13 from 1 takes 12 of the 24
+        // operators being + and the count is 24 choose 12
+        let mut equation = vec![1; 26];
+        equation[0] = 13;
+        assert_eq!(count_operator_matches(&equation, false), 2_704_156);
+
+        let mut equation = vec![1; 42];
+        equation[0] = 21;
+        assert_eq!(count_operator_matches(&equation, false), 137_846_528_820);
+
+        // a zero is counted forwards. * 0 ends at 0, so only 12 + among the first 23 and a
+        // final + 0 reach 13
+        let mut equation = vec![1; 26];
+        equation[0] = 13;
+        equation[25] = 0;
+        assert_eq!(count_operator_matches(&equation, false), 1_352_078);
+    }
+
+    #[test]
+    fn list_operator_matches_test() {
+        assert_eq!(list_operator_matches(&[3267, 81, 40, 27], false, 10), vec!["81 * 40 + 27", "81 + 40 * 27"]);
+        assert_eq!(list_operator_matches(&[3267, 81, 40, 27], false, 1), vec!["81 * 40 + 27"]);
+        assert!(list_operator_matches(&[3267, 81, 40, 27], false, 0).is_empty());
+        assert!(list_operator_matches(&[83, 17, 5], true, 10).is_empty());
+
+        assert_eq!(list_operator_matches(&[44, 2, 2, 2, 2], true, 10), vec!["2 * 2 || 2 + 2", "2 + 2 || 2 + 2"]);
+        assert!(list_operator_matches(&[44, 2, 2, 2, 2], false, 10).is_empty());
 
-        assert_eq!(find_total_calibration_sum_with_concatenation(&calibration_equations), 11387);
+        assert_eq!(list_operator_matches(&[5, 3, 0, 5], true, 10), vec!["3 * 0 + 5", "3 * 0 || 5"]);
     }
 }
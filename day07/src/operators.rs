@@ -0,0 +1,183 @@
+use common::big::BigUint;
+use common::digits::Digits;
+use std::fmt::Display;
+
+// An operator on numbers of type N, i64 unless the equations need something wider
+pub trait Operator<N = i64> {
+    // how the operator is written in a reconstructed expression
+    fn symbol(&self) -> &str;
+
+    // left op right, None if the result is undefined or does not fit
+    fn apply(&self, left: N, right: N) -> Option<N>;
+
+    // the left operand with left op right == result, None if there is none or it is not unique
+    fn inverse(&self, _result: N, _right: N) -> Option<N> {
+        None
+    }
+
+    // whether left op right is never less than left when both are at least 1, which lets a
+    // search drop any partial result that has already passed the target
+    fn is_non_decreasing(&self) -> bool {
+        false
+    }
+}
+
+pub struct Add;
+
+pub struct Multiply;
+
+// digits of the left operand followed by digits of the right, only for non-negative operands
+pub struct Concat;
+
+macro_rules! impl_checked_operators {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(
+            impl Operator<$t> for Add {
+                fn symbol(&self) -> &str {
+                    "+"
+                }
+
+                fn apply(&self, left: $t, right: $t) -> Option<$t> {
+                    left.checked_add(right)
+                }
+
+                fn inverse(&self, result: $t, right: $t) -> Option<$t> {
+                    result.checked_sub(right)
+                }
+
+                fn is_non_decreasing(&self) -> bool {
+                    true
+                }
+            }
+
+            impl Operator<$t> for Multiply {
+                fn symbol(&self) -> &str {
+                    "*"
+                }
+
+                fn apply(&self, left: $t, right: $t) -> Option<$t> {
+                    left.checked_mul(right)
+                }
+
+                fn inverse(&self, result: $t, right: $t) -> Option<$t> {
+                    (right != 0 && result % right == 0).then(|| result / right)
+                }
+
+                fn is_non_decreasing(&self) -> bool {
+                    true
+                }
+            }
+
+            impl Operator<$t> for Concat {
+                fn symbol(&self) -> &str {
+                    "||"
+                }
+
+                fn apply(&self, left: $t, right: $t) -> Option<$t> {
+                    let left = <$unsigned>::try_from(left).ok()?;
+                    let right = <$unsigned>::try_from(right).ok()?;
+
+                    <$t>::try_from(left.concat(right)?).ok()
+                }
+
+                fn inverse(&self, result: $t, right: $t) -> Option<$t> {
+                    let result = <$unsigned>::try_from(result).ok()?;
+                    let right = <$unsigned>::try_from(right).ok()?;
+
+                    <$t>::try_from(result.strip_digit_suffix(right)?).ok()
+                }
+
+                fn is_non_decreasing(&self) -> bool {
+                    true
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_operators!(i64 => u64, u128 => u128);
+
+// never overflows, so apply always gives a result
+impl Operator<BigUint> for Add {
+    fn symbol(&self) -> &str {
+        "+"
+    }
+
+    fn apply(&self, left: BigUint, right: BigUint) -> Option<BigUint> {
+        Some(left.add(&right))
+    }
+
+    fn is_non_decreasing(&self) -> bool {
+        true
+    }
+}
+
+impl Operator<BigUint> for Multiply {
+    fn symbol(&self) -> &str {
+        "*"
+    }
+
+    fn apply(&self, left: BigUint, right: BigUint) -> Option<BigUint> {
+        Some(left.mul(&right))
+    }
+
+    fn is_non_decreasing(&self) -> bool {
+        true
+    }
+}
+
+impl Operator<BigUint> for Concat {
+    fn symbol(&self) -> &str {
+        "||"
+    }
+
+    fn apply(&self, left: BigUint, right: BigUint) -> Option<BigUint> {
+        Some(left.concat(&right))
+    }
+
+    fn is_non_decreasing(&self) -> bool {
+        true
+    }
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Subtract;
+
+impl Operator for Subtract {
+    fn symbol(&self) -> &str {
+        "-"
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_sub(right)
+    }
+
+    fn inverse(&self, result: i64, right: i64) -> Option<i64> {
+        result.checked_add(right)
+    }
+}
+
+// rounds towards zero, so several left operands can give the same result and there is no inverse
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Divide;
+
+impl Operator for Divide {
+    fn symbol(&self) -> &str {
+        "/"
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        left.checked_div(right)
+    }
+}
+
+// Operators are applied strictly left to right, so 81 + 40 * 27 means (81 + 40) * 27
+pub fn expression<N: Display>(values: &[N], operators: &[&dyn Operator<N>]) -> String {
+    let mut expression = values[0].to_string();
+
+    for (operator, value) in operators.iter().zip(&values[1..]) {
+        expression.push_str(&format!(" {} {}", operator.symbol(), value));
+    }
+
+    expression
+}
@@ -0,0 +1,236 @@
+use crate::operators::Operator;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+// Partial results past the target can be dropped when no operator can bring them back down
+fn can_prune<N: PartialOrd + From<u8>>(values: &[N], operators: &[&dyn Operator<N>]) -> bool {
+    let one = N::from(1);
+    operators.iter().all(|operator| operator.is_non_decreasing()) && values.iter().all(|value| *value >= one)
+}
+
+fn to_operators<'o, N>(chosen: &[usize], operators: &[&'o dyn Operator<N>]) -> Vec<&'o dyn Operator<N>> {
+    chosen.iter().map(|&index| operators[index]).collect()
+}
+
+// Calls found with the operator indices of every sequence that takes the values left to right
+// to the target, in the order of the operator list, until found returns false. Results that
+// overflow end their branch
+fn search_forward<N>(target: &N, values: &[N], operators: &[&dyn Operator<N>], mut found: impl FnMut(&[usize]) -> bool)
+where
+    N: Clone + PartialOrd + From<u8>,
+{
+    let Some((first, rest)) = values.split_first() else {
+        return;
+    };
+    let prune = can_prune(values, operators);
+
+    // each entry is a partial result and the operator chosen at each step to reach it
+    let mut stack: Vec<(N, Vec<usize>)> = vec![(first.clone(), Vec::new())];
+
+    while let Some((value, chosen)) = stack.pop() {
+        if prune && value > *target {
+            continue;
+        }
+
+        let Some(next) = rest.get(chosen.len()) else {
+            if value == *target && !found(&chosen) {
+                return;
+            }
+            continue;
+        };
+
+        // pushed in reverse so the first operator is tried first
+        for (index, operator) in operators.iter().enumerate().rev() {
+            if let Some(result) = operator.apply(value.clone(), next.clone()) {
+                let mut chosen = chosen.clone();
+                chosen.push(index);
+                stack.push((result, chosen));
+            }
+        }
+    }
+}
+
+// Multiplying by zero throws away the left operand, so it has no inverse and a backward
+// search would miss every sequence using it. Equations with a zero after the first value are
+// searched forwards instead
+fn has_zero_operand<N: PartialEq + From<u8>>(values: &[N]) -> bool {
+    let zero = N::from(0);
+    values.iter().skip(1).any(|value| *value == zero)
+}
+
+// The first operator sequence, trying operators in the given order, that takes the values
+// left to right to the target. Every value has to be used
+pub fn find_operators<'o, N>(target: N, values: &[N], operators: &[&'o dyn Operator<N>]) -> Option<Vec<&'o dyn Operator<N>>>
+where
+    N: Clone + PartialOrd + From<u8>,
+{
+    all_operators(target, values, operators, 1).pop()
+}
+
+// Up to limit operator sequences reaching the target, in the order find_operators tries them
+pub fn all_operators<'o, N>(
+    target: N,
+    values: &[N],
+    operators: &[&'o dyn Operator<N>],
+    limit: usize,
+) -> Vec<Vec<&'o dyn Operator<N>>>
+where
+    N: Clone + PartialOrd + From<u8>,
+{
+    let mut sequences = Vec::new();
+    if limit == 0 {
+        return sequences;
+    }
+
+    search_forward(&target, values, operators, |chosen| {
+        sequences.push(to_operators(chosen, operators));
+        sequences.len() < limit
+    });
+
+    sequences
+}
+
+// Whether the values up to and including values[remaining] can produce value, pushing the
+// operators used onto chosen from the last one backwards. Many sequences lead to the same
+// state, multiplying by 1 never changes the value at all, so states known to fail are kept
+// in failed and never searched twice
+fn solve_backward<N>(
+    values: &[N],
+    remaining: usize,
+    value: N,
+    operators: &[&dyn Operator<N>],
+    failed: &mut HashSet<(usize, N)>,
+    chosen: &mut Vec<usize>,
+) -> bool
+where
+    N: Clone + Eq + Hash,
+{
+    if remaining == 0 {
+        return value == values[0];
+    }
+    if failed.contains(&(remaining, value.clone())) {
+        return false;
+    }
+
+    for (index, operator) in operators.iter().enumerate() {
+        if let Some(left) = operator.inverse(value.clone(), values[remaining].clone()) {
+            chosen.push(index);
+            if solve_backward(values, remaining - 1, left, operators, failed, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
+    }
+
+    failed.insert((remaining, value));
+    false
+}
+
+// A sequence whenever find_operators finds one, searching backwards from the target unless a
+// value is zero
+pub fn find_operators_backward<'o, N>(
+    target: N,
+    values: &[N],
+    operators: &[&'o dyn Operator<N>],
+) -> Option<Vec<&'o dyn Operator<N>>>
+where
+    N: Clone + Eq + Hash + PartialOrd + From<u8>,
+{
+    if has_zero_operand(values) {
+        return find_operators(target, values, operators);
+    }
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut chosen = Vec::new();
+    if !solve_backward(values, values.len() - 1, target, operators, &mut HashSet::new(), &mut chosen) {
+        return None;
+    }
+    chosen.reverse();
+
+    Some(to_operators(&chosen, operators))
+}
+
+// Number of distinct operator sequences reaching the target, the same ones all_operators
+// lists. Sequences sharing a state are counted once per state rather than walked one by one,
+// so the time depends on the number of distinct partial results and not on the answer.
+// Counts past u128::MAX saturate
+pub fn count_operators<N>(target: N, values: &[N], operators: &[&dyn Operator<N>]) -> u128
+where
+    N: Clone + Eq + Hash + PartialOrd + From<u8>,
+{
+    let Some(first) = values.first() else {
+        return 0;
+    };
+
+    if has_zero_operand(values) {
+        let prune = can_prune(values, operators);
+        count_forward(&target, values, 1, first.clone(), operators, prune, &mut HashMap::new())
+    } else {
+        count_backward(values, values.len() - 1, target, operators, &mut HashMap::new())
+    }
+}
+
+// sequences taking the values up to and including values[remaining] to value
+fn count_backward<N>(
+    values: &[N],
+    remaining: usize,
+    value: N,
+    operators: &[&dyn Operator<N>],
+    memo: &mut HashMap<(usize, N), u128>,
+) -> u128
+where
+    N: Clone + Eq + Hash,
+{
+    if remaining == 0 {
+        return (value == values[0]) as u128;
+    }
+    if let Some(&count) = memo.get(&(remaining, value.clone())) {
+        return count;
+    }
+
+    let mut count: u128 = 0;
+    for operator in operators {
+        if let Some(left) = operator.inverse(value.clone(), values[remaining].clone()) {
+            count = count.saturating_add(count_backward(values, remaining - 1, left, operators, memo));
+        }
+    }
+
+    memo.insert((remaining, value), count);
+    count
+}
+
+// sequences taking value, the result of the values before values[next], on to the target
+fn count_forward<N>(
+    target: &N,
+    values: &[N],
+    next: usize,
+    value: N,
+    operators: &[&dyn Operator<N>],
+    prune: bool,
+    memo: &mut HashMap<(usize, N), u128>,
+) -> u128
+where
+    N: Clone + Eq + Hash + PartialOrd,
+{
+    if prune && value > *target {
+        return 0;
+    }
+    let Some(right) = values.get(next) else {
+        return (value == *target) as u128;
+    };
+    if let Some(&count) = memo.get(&(next, value.clone())) {
+        return count;
+    }
+
+    let mut count: u128 = 0;
+    for operator in operators {
+        if let Some(result) = operator.apply(value.clone(), right.clone()) {
+            count = count.saturating_add(count_forward(target, values, next + 1, result, operators, prune, memo));
+        }
+    }
+
+    memo.insert((next, value), count);
+    count
+}
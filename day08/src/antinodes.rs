@@ -0,0 +1,188 @@
+use crate::calculate_anti_nodes;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// Which points on the line through two antennas of the same frequency count as antinodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // the two points twice as far from one antenna as the other
+    ExactDistance,
+    // the antennas and every whole multiple of the distance between them
+    SteppedByDelta,
+    // every grid point on the line, stepping by the distance divided by its gcd
+    #[cfg_attr(not(test), allow(dead_code))]
+    AllLatticePoints,
+}
+
+// Rectangle of grid points, min inclusive and max exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+impl Bounds {
+    pub fn new(min: (i32, i32), max: (i32, i32)) -> Self {
+        Bounds { min, max }
+    }
+
+    pub fn from_size(width: i32, height: i32) -> Self {
+        Bounds::new((0, 0), (width, height))
+    }
+
+    pub fn contains(&self, coord: &(i32, i32)) -> bool {
+        coord.0 >= self.min.0 && coord.0 < self.max.0 && coord.1 >= self.min.1 && coord.1 < self.max.1
+    }
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn div_floor(a: i64, b: i64) -> i64 {
+    a.div_euclid(b) - if b < 0 && a.rem_euclid(b) != 0 { 1 } else { 0 }
+}
+
+fn div_ceil(a: i64, b: i64) -> i64 {
+    -div_floor(-a, b)
+}
+
+// the range of k for which start + k * step stays within min..max on one axis
+fn axis_range(start: i32, step: i32, min: i32, max: i32) -> (i64, i64) {
+    let low = min as i64 - start as i64;
+    let high = max as i64 - 1 - start as i64;
+
+    match step as i64 {
+        0 if low <= 0 && 0 <= high => (i64::MIN, i64::MAX),
+        0 => (1, 0),
+        step if step > 0 => (div_ceil(low, step), div_floor(high, step)),
+        step => (div_ceil(high, step), div_floor(low, step)),
+    }
+}
+
+// points start + k * step for k from 0 upwards that lie inside the bounds, which need not include start
+fn walk(start: (i32, i32), step: (i32, i32), bounds: &Bounds) -> Vec<(i32, i32)> {
+    let (x_first, x_last) = axis_range(start.0, step.0, bounds.min.0, bounds.max.0);
+    let (y_first, y_last) = axis_range(start.1, step.1, bounds.min.1, bounds.max.1);
+
+    (x_first.max(y_first).max(0)..=x_last.min(y_last))
+        .map(|k| ((start.0 as i64 + k * step.0 as i64) as i32, (start.1 as i64 + k * step.1 as i64) as i32))
+        .collect()
+}
+
+// Antinodes of one pair of antennas inside the bounds, sorted
+pub fn pair_antinodes(first: (i32, i32), second: (i32, i32), mode: Mode, bounds: &Bounds) -> Vec<(i32, i32)> {
+    let delta = (first.0 - second.0, first.1 - second.1);
+    if delta == (0, 0) {
+        return Vec::new();
+    }
+
+    let mut points = match mode {
+        Mode::ExactDistance => calculate_anti_nodes(&first.0, &first.1, &second.0, &second.1),
+        Mode::SteppedByDelta => {
+            let mut points = walk(first, delta, bounds);
+            points.extend(walk(second, (-delta.0, -delta.1), bounds));
+            points
+        }
+        Mode::AllLatticePoints => {
+            let divisor = gcd(delta.0, delta.1);
+            let step = (delta.0 / divisor, delta.1 / divisor);
+            let mut points = walk(first, step, bounds);
+            points.extend(walk((first.0 - step.0, first.1 - step.1), (-step.0, -step.1), bounds));
+            points
+        }
+    };
+
+    points.retain(|point| bounds.contains(point));
+    points.sort();
+    points.dedup();
+    points
+}
+
+// Antinodes of every frequency, each pair of antennas sharing a frequency taken once
+pub fn frequency_antinodes(coords: &[(i32, i32)], mode: Mode, bounds: &Bounds) -> BTreeSet<(i32, i32)> {
+    let mut anti_nodes: BTreeSet<(i32, i32)> = BTreeSet::new();
+
+    for (i, &current) in coords.iter().enumerate() {
+        for &next in &coords[i + 1..] {
+            anti_nodes.extend(pair_antinodes(current, next, mode, bounds));
+        }
+    }
+
+    anti_nodes
+}
+
+pub fn antinodes(nodes: &HashMap<char, Vec<(i32, i32)>>, mode: Mode, bounds: &Bounds) -> BTreeSet<(i32, i32)> {
+    nodes
+        .values()
+        .flat_map(|coords| frequency_antinodes(coords, mode, bounds))
+        .collect()
+}
+
+// Antinodes split by the frequency that produced them
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AntinodeReport {
+    pub by_frequency: BTreeMap<char, BTreeSet<(i32, i32)>>,
+    pub bounds: Bounds,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl AntinodeReport {
+    pub fn new(nodes: &HashMap<char, Vec<(i32, i32)>>, mode: Mode, bounds: &Bounds) -> Self {
+        let by_frequency = nodes
+            .iter()
+            .map(|(&frequency, coords)| (frequency, frequency_antinodes(coords, mode, bounds)))
+            .collect();
+
+        AntinodeReport { by_frequency, bounds: *bounds }
+    }
+
+    pub fn all(&self) -> BTreeSet<(i32, i32)> {
+        self.by_frequency.values().flatten().copied().collect()
+    }
+
+    // distinct locations, a location shared by several frequencies counts once
+    pub fn total(&self) -> usize {
+        self.all().len()
+    }
+
+    // locations produced by more than one frequency, with those frequencies in order
+    pub fn overlaps(&self) -> BTreeMap<(i32, i32), Vec<char>> {
+        let mut frequencies: BTreeMap<(i32, i32), Vec<char>> = BTreeMap::new();
+        for (&frequency, anti_nodes) in &self.by_frequency {
+            for &coord in anti_nodes {
+                frequencies.entry(coord).or_default().push(frequency);
+            }
+        }
+
+        frequencies.retain(|_, frequencies| frequencies.len() > 1);
+        frequencies
+    }
+
+    // The puzzle's picture, one line per row of the bounds. Antennas are drawn over antinodes
+    // and '#' marks antinodes on empty cells
+    pub fn render(&self, nodes: &HashMap<char, Vec<(i32, i32)>>) -> Vec<String> {
+        let width = (self.bounds.max.0 - self.bounds.min.0).max(0) as usize;
+        let height = (self.bounds.max.1 - self.bounds.min.1).max(0) as usize;
+        let mut grid = vec![vec!['.'; width]; height];
+        let cell = |(x, y): (i32, i32)| ((x - self.bounds.min.0) as usize, (y - self.bounds.min.1) as usize);
+
+        for coord in self.all() {
+            let (x, y) = cell(coord);
+            grid[y][x] = '#';
+        }
+
+        for (&frequency, coords) in nodes {
+            for &coord in coords.iter().filter(|coord| self.bounds.contains(coord)) {
+                let (x, y) = cell(coord);
+                grid[y][x] = frequency;
+            }
+        }
+
+        grid.into_iter().map(|row| row.into_iter().collect()).collect()
+    }
+}
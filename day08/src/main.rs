@@ -1,7 +1,10 @@
+use crate::antinodes::{antinodes, Bounds, Mode};
 use common::parse_to_array;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-fn parse_nodes(input: &Vec<String>) -> (HashMap<char, Vec<(i32, i32)>>, (i32, i32)) {
+mod antinodes;
+
+fn parse_nodes(input: &[String]) -> (HashMap<char, Vec<(i32, i32)>>, Bounds) {
     let mut nodes: HashMap<char, Vec<(i32, i32)>> = HashMap::new();
     let max_x = input.first().unwrap().len();
     let max_y = input.len();
@@ -11,13 +14,13 @@ fn parse_nodes(input: &Vec<String>) -> (HashMap<char, Vec<(i32, i32)>>, (i32, i3
             if char != '.' {
                 nodes
                     .entry(char)
-                    .or_insert(Vec::new())
+                    .or_default()
                     .push((char_index as i32, line_index as i32));
             }
         }
     }
 
-    (nodes, (max_x as i32, max_y as i32))
+    (nodes, Bounds::from_size(max_x as i32, max_y as i32))
 }
 
 fn calculate_anti_nodes(x1: &i32, y1: &i32, x2: &i32, y2: &i32) -> Vec<(i32, i32)> {
@@ -33,79 +36,12 @@ fn calculate_anti_nodes(x1: &i32, y1: &i32, x2: &i32, y2: &i32) -> Vec<(i32, i32
     vec![(new_x1, new_y1), (new_x2, new_y2)]
 }
 
-fn is_inbounds(coord: &(i32, i32), bounds: &(i32, i32)) -> bool {
-    coord.0 >= 0 && coord.0 < bounds.0 && coord.1 >= 0 && coord.1 < bounds.1
-}
-
-fn calculate_extended_nodes(x1: &i32, y1: &i32, x2: &i32, y2: &i32, bounds: &(i32, i32)) -> Vec<(i32, i32)> {
-    let x_delta = x1 - x2;
-    let y_delta = y1 - y2;
-
-    let mut anti_nodes = vec![(*x1, *y1), (*x2, *y2)];
-
-    let mut new_x = x1 + x_delta;
-    let mut new_y = y1 + y_delta;
-    while is_inbounds(&(new_x, new_y), bounds) {
-        anti_nodes.push((new_x, new_y));
-        new_x += x_delta;
-        new_y += y_delta;
-    }
-
-    let mut new_x = x2 - x_delta;
-    let mut new_y = y2 - y_delta;
-    while is_inbounds(&(new_x, new_y), bounds) {
-        anti_nodes.push((new_x, new_y));
-        new_x -= x_delta;
-        new_y -= y_delta;
-    }
-
-    anti_nodes
-}
-
-fn count_anti_nodes(nodes: &HashMap<char, Vec<(i32, i32)>>, bounds: &(i32, i32)) -> i32 {
-    // Unique coords, use set.
-    let mut anti_nodes: HashSet<(i32, i32)> = HashSet::new();
-
-    // check all pairs per char by taking first against remaining, second against remaining, etc.
-    for (_char, coords) in nodes {
-        for i in 0..coords.len() {
-            let current = coords[i];
-            for j in i + 1..coords.len() {
-                let next = coords[j];
-                anti_nodes.extend(calculate_anti_nodes(
-                    &current.0, &current.1, &next.0, &next.1,
-                ))
-            }
-        }
-    }
-
-    anti_nodes
-        .iter()
-        .filter(|coord| is_inbounds(*coord, bounds))
-        .count() as i32
+fn count_anti_nodes(nodes: &HashMap<char, Vec<(i32, i32)>>, bounds: &Bounds) -> i32 {
+    antinodes(nodes, Mode::ExactDistance, bounds).len() as i32
 }
 
-fn count_extended_anti_nodes(nodes: &HashMap<char, Vec<(i32, i32)>>, bounds: &(i32, i32)) -> i32 {
-    // Unique coords, use set.
-    let mut anti_nodes: HashSet<(i32, i32)> = HashSet::new();
-
-    // check all pairs per char by taking first against remaining, second against remaining, etc.
-    for (_char, coords) in nodes {
-        for i in 0..coords.len() {
-            let current = coords[i];
-            for j in i + 1..coords.len() {
-                let next = coords[j];
-                anti_nodes.extend(calculate_extended_nodes(
-                    &current.0, &current.1, &next.0, &next.1, &bounds
-                ))
-            }
-        }
-    }
-
-    anti_nodes
-        .iter()
-        .filter(|coord| is_inbounds(*coord, bounds))
-        .count() as i32
+fn count_extended_anti_nodes(nodes: &HashMap<char, Vec<(i32, i32)>>, bounds: &Bounds) -> i32 {
+    antinodes(nodes, Mode::SteppedByDelta, bounds).len() as i32
 }
 
 fn main() {
@@ -122,6 +58,8 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
+    use crate::antinodes::{pair_antinodes, AntinodeReport};
 
     #[test]
     fn anti_node_test() {
@@ -184,4 +122,160 @@ mod tests {
 
         assert_eq!(count_extended_anti_nodes(&nodes, &bounds), 34);
     }
+
+    fn example() -> Vec<String> {
+        vec![
+            "............".to_string(),
+            "........0...".to_string(),
+            ".....0......".to_string(),
+            ".......0....".to_string(),
+            "....0.......".to_string(),
+            "......A.....".to_string(),
+            "............".to_string(),
+            "............".to_string(),
+            "........A...".to_string(),
+            ".........A..".to_string(),
+            "............".to_string(),
+            "............".to_string(),
+        ]
+    }
+
+    #[test]
+    fn exact_distance_mode_test() {
+        let bounds = Bounds::from_size(10, 10);
+
+        assert_eq!(pair_antinodes((4, 3), (5, 5), Mode::ExactDistance, &bounds), vec![(3, 1), (6, 7)]);
+        // one antinode falls off the map
+        assert_eq!(pair_antinodes((0, 0), (2, 4), Mode::ExactDistance, &bounds), vec![(4, 8)]);
+
+        let (nodes, bounds) = parse_nodes(&example());
+        let found = antinodes(&nodes, Mode::ExactDistance, &bounds);
+        assert_eq!(found.len(), 14);
+        assert_eq!(found.first(), Some(&(0, 7)));
+    }
+
+    #[test]
+    fn stepped_by_delta_mode_test() {
+        let bounds = Bounds::from_size(10, 10);
+
+        assert_eq!(
+            pair_antinodes((2, 4), (0, 0), Mode::SteppedByDelta, &bounds),
+            vec![(0, 0), (2, 4), (4, 8)]
+        );
+        assert_eq!(
+            pair_antinodes((0, 0), (3, 1), Mode::SteppedByDelta, &bounds),
+            vec![(0, 0), (3, 1), (6, 2), (9, 3)]
+        );
+    }
+
+    #[test]
+    fn all_lattice_points_mode_test() {
+        let bounds = Bounds::from_size(10, 10);
+
+        // (2, 4) reduces to steps of (1, 2)
+        assert_eq!(
+            pair_antinodes((2, 4), (0, 0), Mode::AllLatticePoints, &bounds),
+            vec![(0, 0), (1, 2), (2, 4), (3, 6), (4, 8)]
+        );
+        // the points between the antennas count too
+        assert_eq!(
+            pair_antinodes((6, 6), (3, 3), Mode::AllLatticePoints, &bounds),
+            (0..10).map(|i| (i, i)).collect::<Vec<(i32, i32)>>()
+        );
+
+        // every delta in the example is already reduced, so it matches part two
+        let (nodes, bounds) = parse_nodes(&example());
+        assert_eq!(antinodes(&nodes, Mode::AllLatticePoints, &bounds).len(), 34);
+    }
+
+    #[test]
+    fn rectangle_bounds_test() {
+        let bounds = Bounds::new((-5, -5), (3, 3));
+
+        assert!(bounds.contains(&(-5, 2)));
+        assert!(!bounds.contains(&(3, 0)));
+        assert_eq!(
+            pair_antinodes((0, 0), (1, 1), Mode::ExactDistance, &bounds),
+            vec![(-1, -1), (2, 2)]
+        );
+        assert_eq!(
+            pair_antinodes((0, 0), (2, 2), Mode::AllLatticePoints, &bounds),
+            (-5..3).map(|i| (i, i)).collect::<Vec<(i32, i32)>>()
+        );
+
+        // the antennas themselves are outside the rectangle
+        let bounds = Bounds::new((3, 3), (6, 6));
+        assert_eq!(
+            pair_antinodes((0, 0), (1, 1), Mode::SteppedByDelta, &bounds),
+            vec![(3, 3), (4, 4), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let (nodes, bounds) = parse_nodes(&example());
+
+        for mode in [Mode::ExactDistance, Mode::SteppedByDelta, Mode::AllLatticePoints] {
+            let expected: Vec<(i32, i32)> = antinodes(&nodes, mode, &bounds).into_iter().collect();
+            for _ in 0..5 {
+                let reparsed = parse_nodes(&example()).0;
+                assert_eq!(antinodes(&reparsed, mode, &bounds).into_iter().collect::<Vec<(i32, i32)>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn antinode_report_test() {
+        let (nodes, bounds) = parse_nodes(&example());
+        let report = AntinodeReport::new(&nodes, Mode::ExactDistance, &bounds);
+
+        assert_eq!(report.by_frequency.keys().copied().collect::<Vec<char>>(), vec!['0', 'A']);
+        assert_eq!(report.by_frequency[&'0'].len(), 10);
+        assert_eq!(report.by_frequency[&'A'].len(), 5);
+        assert_eq!(report.overlaps(), BTreeMap::from([((3, 1), vec!['0', 'A'])]));
+        assert_eq!(report.total(), 14);
+    }
+
+    #[test]
+    fn render_test() {
+        let (nodes, bounds) = parse_nodes(&example());
+
+        let report = AntinodeReport::new(&nodes, Mode::ExactDistance, &bounds);
+        assert_eq!(
+            report.render(&nodes),
+            vec![
+                "......#....#",
+                "...#....0...",
+                "....#0....#.",
+                "..#....0....",
+                "....0....#..",
+                ".#....A.....",
+                "...#........",
+                "#......#....",
+                "........A...",
+                ".........A..",
+                "..........#.",
+                "..........#.",
+            ]
+        );
+
+        let report = AntinodeReport::new(&nodes, Mode::SteppedByDelta, &bounds);
+        assert_eq!(
+            report.render(&nodes),
+            vec![
+                "##....#....#",
+                ".#.#....0...",
+                "..#.#0....#.",
+                "..##...0....",
+                "....0....#..",
+                ".#...#A....#",
+                "...#..#.....",
+                "#....#.#....",
+                "..#.....A...",
+                "....#....A..",
+                ".#........#.",
+                "...#......##",
+            ]
+        );
+    }
 }
@@ -0,0 +1,121 @@
+use crate::disk::{Disk, DiskError, Fit};
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub trait CompactionStrategy {
+    fn name(&self) -> String;
+
+    // compacts the disk in place and returns the number of moves made
+    fn compact(&self, disk: &mut Disk) -> usize;
+}
+
+// Part one, blocks from the end fill the first free block
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct BlockByBlock;
+
+impl CompactionStrategy for BlockByBlock {
+    fn name(&self) -> String {
+        "block by block".to_string()
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        disk.compact_blocks()
+    }
+}
+
+// Part two, each whole file gets one attempt at the leftmost span it fits in
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct FirstFit;
+
+impl CompactionStrategy for FirstFit {
+    fn name(&self) -> String {
+        "first fit".to_string()
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        disk.move_files(Fit::First)
+    }
+}
+
+// Each whole file gets one attempt at the shortest span it fits in
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct BestFit;
+
+impl CompactionStrategy for BestFit {
+    fn name(&self) -> String {
+        "best fit".to_string()
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        disk.move_files(Fit::Best)
+    }
+}
+
+// Each whole file gets one attempt at the longest span left of it
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct WorstFit;
+
+impl CompactionStrategy for WorstFit {
+    fn name(&self) -> String {
+        "worst fit".to_string()
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        disk.move_files(Fit::Worst)
+    }
+}
+
+// Repeats a strategy until a pass moves nothing, so space freed in one pass can be used in the next.
+// Files only ever move left, so this always finishes
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct MultiPass<S: CompactionStrategy>(pub S);
+
+impl<S: CompactionStrategy> CompactionStrategy for MultiPass<S> {
+    fn name(&self) -> String {
+        format!("multi-pass {}", self.0.name())
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut moves = 0;
+
+        loop {
+            let pass_moves = self.0.compact(disk);
+            if pass_moves == 0 {
+                break;
+            }
+            moves += pass_moves;
+        }
+
+        moves
+    }
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactionReport {
+    pub strategy: String,
+    pub checksum: u64,
+    pub moves: usize,
+    pub fragmentation: f64,
+}
+
+// Runs every strategy on its own copy of the disk
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn compare_strategies(
+    disk: &Disk,
+    strategies: &[&dyn CompactionStrategy],
+) -> Result<Vec<CompactionReport>, DiskError> {
+    strategies
+        .iter()
+        .map(|strategy| {
+            let mut compacted = disk.clone();
+            let moves = strategy.compact(&mut compacted);
+
+            Ok(CompactionReport {
+                strategy: strategy.name(),
+                checksum: compacted.checksum()?,
+                moves,
+                fragmentation: compacted.fragmentation(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,374 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiskError {
+    InvalidCharacter { position: usize, character: char },
+    ChecksumOverflow,
+}
+
+impl fmt::Display for DiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskError::InvalidCharacter { position, character } => {
+                write!(f, "disk map has {:?} at position {}, expected a digit", character, position)
+            }
+            DiskError::ChecksumOverflow => write!(f, "checksum overflows u64"),
+        }
+    }
+}
+
+impl Error for DiskError {}
+
+// The run lengths of a disk map, ignoring trailing whitespace such as the input's final newline
+pub fn disk_map_lengths(input: &str) -> Result<Vec<usize>, DiskError> {
+    input
+        .trim_end()
+        .chars()
+        .enumerate()
+        .map(|(position, character)| {
+            character
+                .to_digit(10)
+                .map(|digit| digit as usize)
+                .ok_or(DiskError::InvalidCharacter { position, character })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileExtent {
+    pub id: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpan {
+    pub start: usize,
+    pub len: usize,
+}
+
+// Which free span a whole file moves into, among those left of it that are long enough
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    First,
+    #[cfg_attr(not(test), allow(dead_code))]
+    Best,
+    #[cfg_attr(not(test), allow(dead_code))]
+    Worst,
+}
+
+// One block holding different contents in two layouts, None for free space
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChange {
+    pub position: usize,
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+}
+
+// Disk layout stored as runs of blocks rather than one entry per block.
+// Files and free spans are both kept sorted by start, a file split by block
+// compaction appears as several extents with the same id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    pub files: Vec<FileExtent>,
+    pub free: Vec<FreeSpan>,
+    pub size: usize,
+}
+
+impl Disk {
+    // Alternating file and free lengths, file ids counting up from 0
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn from_disk_map(input: &str) -> Result<Disk, DiskError> {
+        let mut files: Vec<FileExtent> = Vec::new();
+        let mut free: Vec<FreeSpan> = Vec::new();
+        let mut position = 0;
+
+        for (index, len) in disk_map_lengths(input)?.into_iter().enumerate() {
+            if index % 2 == 0 {
+                files.push(FileExtent { id: index / 2, start: position, len });
+            } else if len > 0 {
+                // an empty file between two gaps leaves one longer gap
+                match free.last_mut() {
+                    Some(span) if span.start + span.len == position => span.len += len,
+                    _ => free.push(FreeSpan { start: position, len }),
+                }
+            }
+            position += len;
+        }
+
+        files.retain(|file| file.len > 0);
+
+        Ok(Disk { files, free, size: position })
+    }
+
+    // From one entry per block with -1 for free space, as produced by parse_filesystem
+    pub fn from_blocks(blocks: &[i32]) -> Disk {
+        let mut files: Vec<FileExtent> = Vec::new();
+        let mut free: Vec<FreeSpan> = Vec::new();
+
+        for (position, &block) in blocks.iter().enumerate() {
+            if block == -1 {
+                match free.last_mut() {
+                    Some(span) if span.start + span.len == position => span.len += 1,
+                    _ => free.push(FreeSpan { start: position, len: 1 }),
+                }
+            } else {
+                match files.last_mut() {
+                    Some(file) if file.id == block as usize && file.start + file.len == position => file.len += 1,
+                    _ => files.push(FileExtent { id: block as usize, start: position, len: 1 }),
+                }
+            }
+        }
+
+        Disk { files, free, size: blocks.len() }
+    }
+
+    pub fn to_blocks(&self) -> Vec<i32> {
+        let mut blocks = vec![-1; self.size];
+
+        for file in &self.files {
+            for block in &mut blocks[file.start..file.start + file.len] {
+                *block = file.id as i32;
+            }
+        }
+
+        blocks
+    }
+
+    // Inverse of from_disk_map. Only layouts where every file is one extent, files appear
+    // in id order and no run is longer than 9 blocks can be written as a disk map
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn to_disk_map(&self) -> Option<String> {
+        // alternating file and gap lengths, always ending on a gap
+        let mut lengths: Vec<usize> = Vec::new();
+        let mut position = 0;
+        let mut next_id = 0;
+        let mut first_gap_slot = 1;
+
+        for file in &self.files {
+            if file.id < next_id {
+                return None;
+            }
+
+            // ids missing from the layout were empty files
+            for _ in next_id..file.id {
+                lengths.push(0);
+                lengths.push(0);
+            }
+
+            // spread the gap over the slots around any empty files, at most 9 blocks each
+            let mut gap = file.start - position;
+            for slot in lengths.iter_mut().skip(first_gap_slot).step_by(2) {
+                let filled = gap.min(9);
+                *slot += filled;
+                gap -= filled;
+            }
+            if gap > 0 {
+                return None;
+            }
+
+            lengths.push(file.len);
+            lengths.push(0);
+            first_gap_slot = lengths.len() - 1;
+            position = file.start + file.len;
+            next_id = file.id + 1;
+        }
+
+        if lengths.is_empty() {
+            lengths.extend([0, 0]);
+        }
+        *lengths.last_mut().unwrap() += self.size - position;
+        if lengths.last() == Some(&0) {
+            lengths.pop();
+        }
+
+        lengths
+            .into_iter()
+            .map(|len| char::from_digit(u32::try_from(len).ok()?, 10))
+            .collect()
+    }
+
+    // Every block whose contents differ between the two layouts
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn diff(&self, other: &Disk) -> Vec<BlockChange> {
+        let before = self.to_blocks();
+        let after = other.to_blocks();
+        let as_file = |block: Option<&i32>| block.filter(|&&id| id != -1).map(|&id| id as usize);
+
+        (0..before.len().max(after.len()))
+            .map(|position| BlockChange {
+                position,
+                before: as_file(before.get(position)),
+                after: as_file(after.get(position)),
+            })
+            .filter(|change| change.before != change.after)
+            .collect()
+    }
+
+    // Moves single blocks from the end of the disk into the leftmost free block until no gaps
+    // remain. Returns the number of block runs moved
+    pub fn compact_blocks(&mut self) -> usize {
+        let mut free: VecDeque<FreeSpan> = self.free.iter().copied().collect();
+        let mut files = std::mem::take(&mut self.files);
+        let mut placed: Vec<FileExtent> = Vec::with_capacity(files.len() + free.len());
+        let mut moves = 0;
+
+        while let Some(mut file) = files.pop() {
+            while let Some(span) = free.front_mut() {
+                if span.start >= file.start || file.len == 0 {
+                    break;
+                }
+
+                // the tail of the file moves, so whatever stays keeps its start
+                let moved = span.len.min(file.len);
+                placed.push(FileExtent { id: file.id, start: span.start, len: moved });
+                moves += 1;
+                file.len -= moved;
+                span.start += moved;
+                span.len -= moved;
+
+                if span.len == 0 {
+                    free.pop_front();
+                }
+            }
+
+            if file.len > 0 {
+                // nothing free is left of this file, so nothing is left of the files before it either
+                placed.push(file);
+                placed.append(&mut files);
+            }
+        }
+
+        self.files = placed;
+        self.files.sort_by_key(|file| file.start);
+        self.rebuild_free();
+
+        moves
+    }
+
+    // Moves each whole file, highest id first, into the leftmost free span it fits in
+    pub fn compact_files(&mut self) -> usize {
+        self.move_files(Fit::First)
+    }
+
+    // Moves each whole file once, highest id first, into a free span left of it chosen by fit.
+    // Free spans are kept in one min-heap of starts per span length, so finding a span only
+    // looks at the top of each heap. Space freed by a move is not reused within the same call.
+    // Returns the number of files moved
+    pub fn move_files(&mut self, fit: Fit) -> usize {
+        let longest = self.free.iter().map(|span| span.len).max().unwrap_or(0);
+        let mut spans_by_len: Vec<BinaryHeap<Reverse<usize>>> = vec![BinaryHeap::new(); longest + 1];
+        for span in &self.free {
+            spans_by_len[span.len].push(Reverse(span.start));
+        }
+
+        let mut order: Vec<usize> = (0..self.files.len()).collect();
+        order.sort_by_key(|&index| Reverse((self.files[index].id, self.files[index].start)));
+
+        let mut moves = 0;
+        for index in order {
+            let file = self.files[index];
+            if file.len > longest {
+                continue;
+            }
+
+            // leftmost span of each length that is still left of the file
+            let candidates = (file.len..=longest).filter_map(|len| {
+                spans_by_len[len]
+                    .peek()
+                    .map(|&Reverse(start)| (start, len))
+                    .filter(|&(start, _)| start < file.start)
+            });
+
+            let chosen = match fit {
+                Fit::First => candidates.min(),
+                Fit::Best => candidates.min_by_key(|&(_, len)| len),
+                Fit::Worst => candidates.max_by_key(|&(start, len)| (len, Reverse(start))),
+            };
+
+            if let Some((start, len)) = chosen {
+                spans_by_len[len].pop();
+                if len > file.len {
+                    spans_by_len[len - file.len].push(Reverse(start + file.len));
+                }
+                self.files[index].start = start;
+                moves += 1;
+            }
+        }
+
+        self.files.sort_by_key(|file| file.start);
+        self.rebuild_free();
+
+        moves
+    }
+
+    // 1 minus the share of free space in the largest free span, 0 when all free space is together
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn fragmentation(&self) -> f64 {
+        let total: usize = self.free.iter().map(|span| span.len).sum();
+        let largest = self.free.iter().map(|span| span.len).max().unwrap_or(0);
+
+        if total == 0 {
+            return 0.0;
+        }
+
+        1.0 - largest as f64 / total as f64
+    }
+
+    fn rebuild_free(&mut self) {
+        self.free.clear();
+        let mut position = 0;
+
+        for file in &self.files {
+            if file.start > position {
+                self.free.push(FreeSpan { start: position, len: file.start - position });
+            }
+            position = file.start + file.len;
+        }
+
+        if self.size > position {
+            self.free.push(FreeSpan { start: position, len: self.size - position });
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn checksum(&self) -> Result<u64, DiskError> {
+        self.files.iter().try_fold(0_u64, |total, file| {
+            let (id, start, len) = (file.id as u64, file.start as u64, file.len as u64);
+
+            // sum of positions start..start + len. One of len and len - 1 is even, halving that
+            // one first keeps len * (len - 1) / 2 from overflowing before the division
+            let below = len.saturating_sub(1);
+            let triangle = if len.is_multiple_of(2) { (len / 2).checked_mul(below) } else { len.checked_mul(below / 2) };
+            let positions = start
+                .checked_mul(len)
+                .zip(triangle)
+                .and_then(|(offset, triangle)| offset.checked_add(triangle));
+
+            positions
+                .and_then(|positions| id.checked_mul(positions))
+                .and_then(|value| total.checked_add(value))
+                .ok_or(DiskError::ChecksumOverflow)
+        })
+    }
+}
+
+// The puzzle's picture of a disk, '.' for free blocks and the last digit of the file id otherwise
+impl fmt::Display for Disk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered: String = self
+            .to_blocks()
+            .into_iter()
+            .map(|block| match block {
+                -1 => '.',
+                id => char::from_digit(id as u32 % 10, 10).unwrap(),
+            })
+            .collect();
+
+        f.write_str(&rendered)
+    }
+}
@@ -1,15 +1,16 @@
-use itertools::Itertools;
-use std::ops::Index;
+use crate::disk::{disk_map_lengths, Disk, DiskError};
 use common::parse_to_text;
 
+mod compaction;
+mod disk;
+
 // Convert to num array where spaces are -1
-fn parse_filesystem(input: &String) -> Vec<i32> {
+fn parse_filesystem(input: &str) -> Result<Vec<i32>, DiskError> {
     let mut is_space = false;
     let mut number: i32 = 0;
     let mut file_system: Vec<i32> = Vec::new();
 
-    for char in input.chars() {
-        let size: usize = char.to_digit(10).unwrap() as usize;
+    for size in disk_map_lengths(input)? {
         if !is_space {
             for _ in 0..size {
                 file_system.push(number);
@@ -23,160 +24,69 @@ fn parse_filesystem(input: &String) -> Vec<i32> {
         is_space = !is_space;
     }
 
-    file_system
+    Ok(file_system)
 }
 
 // Sort last i32 into first space
-fn defrag_system(filesystem: &Vec<i32>) -> Vec<i32> {
-    let mut defragged: Vec<i32> = Vec::new();
-    let mut count_from_end = 1; // for scanning
-    let mut swap_count = 0; // for truncating
-
-    for data_block in filesystem {
-        if *data_block != -1 { // if data, push
-            defragged.push(*data_block);
-        } else { // if not data
-            let mut found = false;
-
-            while count_from_end <= filesystem.len() && !found { // seek from end until data found
-                let next = filesystem[filesystem.len() - count_from_end];
-                if next != -1 { // if data, push to output
-                    defragged.push(next);
-                    found = true;
-                    swap_count += 1;
-                }
-                count_from_end += 1;
-            }
-        }
-    }
+fn defrag_system(filesystem: &[i32]) -> Vec<i32> {
+    let mut disk = Disk::from_blocks(filesystem);
+    disk.compact_blocks();
 
-    defragged.truncate(defragged.len() - swap_count); // 0, -1, 2 becomes 0, 2, 2. Remove copied data at end
+    let mut defragged = disk.to_blocks();
+    defragged.truncate(defragged.len() - disk.free.last().map_or(0, |span| span.len)); // drop free space at the end
     defragged
 }
 
-// Get a vector slice for the start/end of a data block to move
-fn get_last_to_move(file_number: i32, input: &mut Vec<i32>) -> (i32, i32) {
-    let mut start_index = -1;
-    let mut end_index = -1;
-
-    for (i, &number) in input.iter().enumerate() {
-        if number == file_number {
-            if start_index == -1 {
-                start_index = i as i32;
-            }
-            end_index = i as i32 + 1;
-        }
-    }
-
-    (start_index, end_index) // -1, -1 if none found
-}
-
-// Same as the above but go left to right and find first slice big enough to fit size
-fn get_first_space_to_fit(size: i32, max_index: i32, input: &mut Vec<i32>) -> (i32, i32) {
-    let mut count = 0;
-    let mut start_index: Option<usize> = None;
-
-    for (i, number) in input.iter().enumerate() {
-        if i as i32 == max_index {
-            break;
-        }
-        if *number == -1 {
-            if start_index.is_none() {
-                start_index = Some(i);
-            }
-            count += 1;
-        } else {
-            start_index = None;
-            count = 0;
-        }
-
-        if count >= size {
-            return (start_index.unwrap() as i32, (start_index.unwrap() as i32 + size));
-        }
-    }
-
-    (-1, -1) // -1, -1 if none found
-}
-
-fn swap_slices(vec: &mut Vec<i32>, start1: usize, end1: usize, start2: usize, end2: usize) {
-    let slice1 = &vec[start1..end1];
-    let slice2 = &vec[start2..end2];
-
-    let mut swapped_vec = Vec::new();
-
-    swapped_vec.extend(&vec[..start1]);
-    swapped_vec.extend(slice2.iter().copied());
-    swapped_vec.extend(&vec[end1..start2]);
-    swapped_vec.extend(slice1.iter().copied());
-    swapped_vec.extend(&vec[end2..]);
-
-    *vec = swapped_vec;
-}
-
 fn block_defrag_system(input: &mut Vec<i32>) -> Vec<i32> {
-    // Work from last file (biggest numbered file)
-    let mut file_number = *input.iter().max().unwrap();
-
-    loop {
-        // Slice (block) for file number
-        let (block_start, block_end) = get_last_to_move(file_number, input);
-
-        if block_start == -1 && block_end == -1 {
-            break;
-        }
-
-        let block_size = block_end - block_start;
-        // Check from 0 to start of block for a space
-        let (space_start, space_end) = get_first_space_to_fit(block_size, block_start, input);
-
-        // If found, swap
-        if space_start != -1 {
-            swap_slices(input, space_start as usize, space_end as usize, block_start as usize, block_end as usize);
-        }
-
-        file_number -= 1;
-        if file_number < 0 {
-            break
-        }
-    }
+    let mut disk = Disk::from_blocks(input);
+    disk.compact_files();
 
+    *input = disk.to_blocks();
     input.clone()
 }
 
-fn generate_checksum(defragged_system: &Vec<i32>) -> i64 {
-    let mut result = 0;
+fn generate_checksum(defragged_system: &[i32]) -> Result<u64, DiskError> {
+    let mut result: u64 = 0;
 
     for (index, id) in defragged_system.iter().enumerate() {
         if *id == -1 {
             continue;
         }
-        result += (index as i32 * id) as i64;
+        result = (index as u64)
+            .checked_mul(*id as u64)
+            .and_then(|value| result.checked_add(value))
+            .ok_or(DiskError::ChecksumOverflow)?;
     }
 
-    result
+    Ok(result)
 }
 
 fn main() {
     let input = parse_to_text("day09part01.txt").unwrap();
-    let mut filesystem = parse_filesystem(&input);
+    let mut filesystem = parse_filesystem(&input).unwrap();
     let defragged_filesystem = defrag_system(&filesystem);
 
-    let part_one = generate_checksum(&defragged_filesystem);
+    let part_one = generate_checksum(&defragged_filesystem).unwrap();
     println!("{}", part_one);
 
     let block_defragged_filesystem = block_defrag_system(&mut filesystem);
-    let part_two = generate_checksum(&block_defragged_filesystem);
+    let part_two = generate_checksum(&block_defragged_filesystem).unwrap();
     println!("{}", part_two);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::compaction::{
+        compare_strategies, BestFit, BlockByBlock, CompactionStrategy, FirstFit, MultiPass, WorstFit,
+    };
+    use crate::disk::{BlockChange, FileExtent};
+    use common::random::SeededRng;
 
     #[test]
     fn defrag_test() {
         let input = "2333133121414131402".to_string();
-        let filesystem = parse_filesystem(&input);
+        let filesystem = parse_filesystem(&input).unwrap();
 
         assert_eq!(
             defrag_system(&filesystem),
@@ -189,7 +99,7 @@ mod tests {
     #[test]
     fn block_defrag_test() {
         let input = "2333133121414131402".to_string();
-        let filesystem = &mut parse_filesystem(&input);
+        let filesystem = &mut parse_filesystem(&input).unwrap();
 
         assert_eq!(
             block_defrag_system(filesystem),
@@ -202,16 +112,285 @@ mod tests {
     #[test]
     fn part_one_test() {
         let input = "2333133121414131402".to_string();
-        let defragged_filesystem = defrag_system(&parse_filesystem(&input));
+        let defragged_filesystem = defrag_system(&parse_filesystem(&input).unwrap());
 
-        assert_eq!(generate_checksum(&defragged_filesystem), 1928);
+        assert_eq!(generate_checksum(&defragged_filesystem).unwrap(), 1928);
     }
 
     #[test]
     fn part_two_test() {
         let input = "2333133121414131402".to_string();
-        let defragged_filesystem = block_defrag_system(&mut parse_filesystem(&input));
+        let defragged_filesystem = block_defrag_system(&mut parse_filesystem(&input).unwrap());
+
+        assert_eq!(generate_checksum(&defragged_filesystem).unwrap(), 2858);
+    }
+
+    #[test]
+    fn disk_display_test() {
+        let mut disk = Disk::from_disk_map("2333133121414131402").unwrap();
+        assert_eq!(disk.to_string(), "00...111...2...333.44.5555.6666.777.888899");
+
+        disk.compact_files();
+        assert_eq!(disk.to_string(), "00992111777.44.333....5555.6666.....8888..");
+
+        let mut disk = Disk::from_disk_map("12345").unwrap();
+        disk.compact_blocks();
+        assert_eq!(disk.to_string(), "022111222......");
+    }
+
+    #[test]
+    fn disk_map_round_trip_test() {
+        for input in ["2333133121414131402", "12345", "90909", "1010101", "3003", "0520"] {
+            let disk = Disk::from_disk_map(input).unwrap();
+            let disk_map = disk.to_disk_map().unwrap();
+            assert_eq!(Disk::from_disk_map(&disk_map).unwrap(), disk);
+        }
+        assert_eq!(Disk::from_disk_map("2333133121414131402").unwrap().to_disk_map().unwrap(), "2333133121414131402");
+
+        for seed in 0..10 {
+            let input = generated_disk_map(101, seed);
+            let disk = Disk::from_disk_map(&input).unwrap();
+            assert_eq!(Disk::from_disk_map(&disk.to_disk_map().unwrap()).unwrap(), disk);
+        }
+
+        // files end up out of id order once moved
+        let mut disk = Disk::from_disk_map("2333133121414131402").unwrap();
+        disk.compact_files();
+        assert_eq!(disk.to_disk_map(), None);
+
+        // a gap of ten blocks has no single digit
+        let disk = Disk::from_blocks(&[0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 1]);
+        assert_eq!(disk.to_disk_map(), None);
+    }
+
+    #[test]
+    fn disk_diff_test() {
+        let before = Disk::from_disk_map("12345").unwrap();
+        let mut after = before.clone();
+        after.compact_blocks();
+
+        assert_eq!(before.diff(&before), vec![]);
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                BlockChange { position: 1, before: None, after: Some(2) },
+                BlockChange { position: 2, before: None, after: Some(2) },
+                BlockChange { position: 6, before: None, after: Some(2) },
+                BlockChange { position: 7, before: None, after: Some(2) },
+                BlockChange { position: 8, before: None, after: Some(2) },
+                BlockChange { position: 10, before: Some(2), after: None },
+                BlockChange { position: 11, before: Some(2), after: None },
+                BlockChange { position: 12, before: Some(2), after: None },
+                BlockChange { position: 13, before: Some(2), after: None },
+                BlockChange { position: 14, before: Some(2), after: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn fit_strategies_test() {
+        let disk = Disk::from_disk_map("13122").unwrap();
+        assert_eq!(disk.to_string(), "0...1..22");
+
+        let mut first = disk.clone();
+        assert_eq!(FirstFit.compact(&mut first), 2);
+        assert_eq!(first.to_string(), "0221.....");
+
+        let mut best = disk.clone();
+        assert_eq!(BestFit.compact(&mut best), 2);
+        assert_eq!(best.to_string(), "01...22..");
+
+        let mut worst = disk.clone();
+        assert_eq!(WorstFit.compact(&mut worst), 2);
+        assert_eq!(worst.to_string(), "0221.....");
+
+        let mut blocks = disk.clone();
+        assert_eq!(BlockByBlock.compact(&mut blocks), 2);
+        assert_eq!(blocks.to_string(), "0221.....");
+    }
 
-        assert_eq!(generate_checksum(&defragged_filesystem), 2858);
+    #[test]
+    fn multi_pass_test() {
+        let disk = Disk::from_disk_map("12113").unwrap();
+        assert_eq!(disk.to_string(), "0..1.222");
+
+        let mut single = disk.clone();
+        assert_eq!(FirstFit.compact(&mut single), 1);
+        assert_eq!(single.to_string(), "01...222");
+
+        let mut multi = disk.clone();
+        assert_eq!(MultiPass(FirstFit).compact(&mut multi), 2);
+        assert_eq!(multi.to_string(), "01222...");
+    }
+
+    #[test]
+    fn compare_strategies_test() {
+        let disk = Disk::from_disk_map("2333133121414131402").unwrap();
+        let reports = compare_strategies(
+            &disk,
+            &[&BlockByBlock, &FirstFit, &BestFit, &WorstFit, &MultiPass(FirstFit)],
+        )
+        .unwrap();
+
+        let names: Vec<&str> = reports.iter().map(|report| report.strategy.as_str()).collect();
+        assert_eq!(names, vec!["block by block", "first fit", "best fit", "worst fit", "multi-pass first fit"]);
+
+        assert_eq!(reports[0].checksum, 1928);
+        assert_eq!(reports[0].fragmentation, 0.0);
+        assert_eq!(reports[1].checksum, 2858);
+        assert_eq!(reports[1].moves, 4);
+        assert!(reports[1].fragmentation > 0.0);
+
+        for report in &reports[2..] {
+            let mut compacted = disk.clone();
+            assert!(report.moves > 0);
+            assert!(report.checksum < compacted.checksum().unwrap());
+            assert!((0.0..1.0).contains(&report.fragmentation));
+            compacted.compact_blocks();
+            assert!(report.checksum >= compacted.checksum().unwrap());
+        }
+    }
+
+    // Straightforward whole file first fit over single blocks, to check the heap based version against
+    fn reference_block_defrag(blocks: &mut [i32]) {
+        let highest = blocks.iter().copied().max().unwrap_or(-1);
+
+        for id in (0..=highest).rev() {
+            let Some(start) = blocks.iter().position(|&block| block == id) else {
+                continue;
+            };
+            let len = blocks[start..].iter().take_while(|&&block| block == id).count();
+
+            let mut run = 0;
+            for position in 0..start {
+                run = if blocks[position] == -1 { run + 1 } else { 0 };
+                if run == len {
+                    let target = position + 1 - len;
+                    blocks[target..target + len].fill(id);
+                    blocks[start..start + len].fill(-1);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn generated_disk_map(digits: usize, seed: u64) -> String {
+        let mut rng = SeededRng::new(seed);
+        (0..digits).map(|_| char::from_digit(rng.below(10) as u32, 10).unwrap()).collect()
+    }
+
+    #[test]
+    fn disk_matches_blocks_test() {
+        let input = "2333133121414131402";
+        let disk = Disk::from_disk_map(input).unwrap();
+
+        assert_eq!(disk.to_blocks(), parse_filesystem(input).unwrap());
+        assert_eq!(disk, Disk::from_blocks(&parse_filesystem(input).unwrap()));
+
+        let mut compacted = disk.clone();
+        compacted.compact_blocks();
+        assert_eq!(compacted.checksum().unwrap(), 1928);
+
+        let mut compacted = disk.clone();
+        compacted.compact_files();
+        assert_eq!(compacted.checksum().unwrap(), 2858);
+    }
+
+    #[test]
+    fn compact_files_matches_reference_test() {
+        for seed in 0..20 {
+            let input = generated_disk_map(301, seed);
+            let mut blocks = parse_filesystem(&input).unwrap();
+
+            let mut disk = Disk::from_disk_map(&input).unwrap();
+            disk.compact_files();
+
+            reference_block_defrag(&mut blocks);
+            assert_eq!(disk.to_blocks(), blocks);
+            assert_eq!(disk.checksum().unwrap(), generate_checksum(&blocks).unwrap());
+
+            let mut disk = Disk::from_disk_map(&input).unwrap();
+            disk.compact_blocks();
+            assert_eq!(disk.to_blocks(), defrag_system(&parse_filesystem(&input).unwrap()).into_iter()
+                .chain(std::iter::repeat(-1)).take(disk.size).collect::<Vec<i32>>());
+        }
+    }
+
+    #[test]
+    fn large_disk_test() {
+        // roughly two million blocks
+        let input = generated_disk_map(450_000, 7);
+        let mut disk = Disk::from_disk_map(&input).unwrap();
+        let files = disk.files.len();
+        let used: usize = disk.files.iter().map(|file| file.len).sum();
+
+        disk.compact_files();
+        assert_eq!(disk.files.len(), files);
+        assert_eq!(disk.files.iter().map(|file| file.len).sum::<usize>(), used);
+        assert!(disk.files.windows(2).all(|pair| pair[0].start + pair[0].len <= pair[1].start));
+
+        let mut disk = Disk::from_disk_map(&input).unwrap();
+        disk.compact_blocks();
+        assert_eq!(disk.free.len(), 1);
+        assert_eq!(disk.free[0].start, used);
+    }
+
+    #[test]
+    fn parse_validation_test() {
+        assert_eq!(parse_filesystem("2333133121414131402\n").unwrap(), parse_filesystem("2333133121414131402").unwrap());
+        assert_eq!(Disk::from_disk_map("12345\r\n").unwrap(), Disk::from_disk_map("12345").unwrap());
+
+        let error = DiskError::InvalidCharacter { position: 3, character: 'x' };
+        assert_eq!(parse_filesystem("123x5"), Err(error.clone()));
+        assert_eq!(Disk::from_disk_map("123x5"), Err(error));
+        assert_eq!(
+            Disk::from_disk_map("12 45"),
+            Err(DiskError::InvalidCharacter { position: 2, character: ' ' })
+        );
+    }
+
+    #[test]
+    fn checksum_overflow_test() {
+        let disk = Disk {
+            files: vec![FileExtent { id: usize::MAX / 2, start: 10, len: 1 }],
+            free: Vec::new(),
+            size: 11,
+        };
+        assert_eq!(disk.checksum(), Err(DiskError::ChecksumOverflow));
+
+        // len * (len - 1) is past u64 but half of it is not
+        let len = (1 << 32) + 1;
+        let disk = Disk {
+            files: vec![FileExtent { id: 1, start: 0, len }],
+            free: Vec::new(),
+            size: len,
+        };
+        assert_eq!(disk.checksum(), Ok((1 << 63) + (1 << 31)));
+
+        let disk = Disk {
+            files: vec![FileExtent { id: 1, start: 0, len: 1 << 33 }],
+            free: Vec::new(),
+            size: 1 << 33,
+        };
+        assert_eq!(disk.checksum(), Err(DiskError::ChecksumOverflow));
+    }
+
+    #[test]
+    fn large_checksum_test() {
+        // past i32::MAX blocks times ids, checked against a sum done in u128
+        let input = generated_disk_map(450_000, 11);
+        let mut disk = Disk::from_disk_map(&input).unwrap();
+        disk.compact_files();
+
+        let expected: u128 = disk
+            .to_blocks()
+            .iter()
+            .enumerate()
+            .filter(|(_, &id)| id != -1)
+            .map(|(position, &id)| position as u128 * id as u128)
+            .sum();
+        assert!(expected > i32::MAX as u128);
+        assert_eq!(disk.checksum().unwrap() as u128, expected);
+        assert_eq!(generate_checksum(&disk.to_blocks()).unwrap() as u128, expected);
     }
 }
@@ -0,0 +1,56 @@
+use std::fmt::Write;
+
+#[cfg_attr(not(test), allow(dead_code))]
+const RAMP: [char; 9] = [' ', '.', ':', '-', '=', '+', '*', '%', '@'];
+
+// any trail at all gets at least the first visible level, the busiest cells get the last
+#[cfg_attr(not(test), allow(dead_code))]
+fn level(count: usize, max: usize, levels: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    if max <= 1 {
+        return levels - 1;
+    }
+
+    1 + (count - 1) * (levels - 2) / (max - 1)
+}
+
+// one character per cell, blank where no trail passes and '@' for the busiest cells
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn render_ascii(heatmap: &[Vec<usize>]) -> String {
+    let max = heatmap.iter().flatten().copied().max().unwrap_or(0);
+    let mut output = String::new();
+
+    for row in heatmap {
+        for &count in row {
+            output.push(RAMP[level(count, max, RAMP.len())]);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+// plain text PPM, grey levels scale with trail count and impassable cells are drawn dark blue
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn render_ppm(mountain: &[Vec<Option<usize>>], heatmap: &[Vec<usize>]) -> String {
+    let max = heatmap.iter().flatten().copied().max().unwrap_or(0);
+    let width = heatmap.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut output = format!("P3\n{} {}\n255\n", width, heatmap.len());
+
+    for (y, row) in heatmap.iter().enumerate() {
+        let pixels: Vec<String> = (0..width)
+            .map(|x| match (mountain[y].get(x), row.get(x)) {
+                (Some(Some(_)), Some(&count)) => {
+                    let grey = (count * 255).checked_div(max).unwrap_or(0);
+                    format!("{} {} {}", grey, grey, grey)
+                }
+                _ => "0 0 128".to_string(),
+            })
+            .collect();
+        writeln!(output, "{}", pixels.join(" ")).unwrap();
+    }
+
+    output
+}
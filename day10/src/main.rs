@@ -1,13 +1,18 @@
-use std::collections::{HashSet, VecDeque};
+use crate::trails::{HeightRules, Trails};
 use common::parse_to_array;
+use std::error::Error;
 
-fn parse_mountain(input: &Vec<String>) -> Vec<Vec<usize>> {
-    let mut mountain: Vec<Vec<usize>> = Vec::new();
+mod heatmap;
+mod trails;
+
+// impassable cells are None
+fn parse_mountain(input: &[String]) -> Vec<Vec<Option<usize>>> {
+    let mut mountain: Vec<Vec<Option<usize>>> = Vec::new();
 
     for line in input {
-        let mut vec: Vec<usize> = Vec::new();
+        let mut vec: Vec<Option<usize>> = Vec::new();
         for char in line.chars() {
-            let number: usize = char.to_digit(10).unwrap_or(11) as usize;
+            let number: Option<usize> = char.to_digit(10).map(|digit| digit as usize);
             vec.push(number);
         }
         mountain.push(vec);
@@ -16,8 +21,38 @@ fn parse_mountain(input: &Vec<String>) -> Vec<Vec<usize>> {
     mountain
 }
 
-fn get_valid_neighbors(mountain: &Vec<Vec<usize>>, x: usize, y: usize) -> Vec<(usize, usize)> {
-    let value = mountain[y][x];
+// rows of comma separated heights of any size, with '.', '#' or an empty field for impassable cells
+#[cfg_attr(not(test), allow(dead_code))]
+fn parse_elevation_rows(input: &[String]) -> Result<Vec<Vec<Option<usize>>>, Box<dyn Error>> {
+    let mut mountain: Vec<Vec<Option<usize>>> = Vec::new();
+
+    for (line_index, line) in input.iter().enumerate() {
+        let mut vec: Vec<Option<usize>> = Vec::new();
+        for field in line.split(',') {
+            let field = field.trim();
+            let height = match field {
+                "" | "." | "#" => None,
+                _ => Some(field.parse::<usize>().map_err(|e| {
+                    format!("line {}: invalid height '{}': {}", line_index + 1, field, e)
+                })?),
+            };
+            vec.push(height);
+        }
+        mountain.push(vec);
+    }
+
+    Ok(mountain)
+}
+
+fn get_valid_neighbors(
+    mountain: &[Vec<Option<usize>>],
+    rules: &HeightRules,
+    x: usize,
+    y: usize,
+) -> Vec<(usize, usize)> {
+    let Some(value) = mountain[y][x] else {
+        return Vec::new();
+    };
 
     let mut neighbors: Vec<(usize, usize)> = Vec::new();
 
@@ -26,73 +61,34 @@ fn get_valid_neighbors(mountain: &Vec<Vec<usize>>, x: usize, y: usize) -> Vec<(u
         let x_new = x as i32 + dx;
         let y_new = y as i32 + dy;
         if x_new >= 0
-            && x_new < mountain[0].len() as i32
             && y_new >= 0
             && y_new < mountain.len() as i32
+            && x_new < mountain[y_new as usize].len() as i32
+            && mountain[y_new as usize][x_new as usize].is_some_and(|next| rules.allows(value, next))
         {
-            if mountain[y_new as usize][x_new as usize] == value + 1 {
-                neighbors.push((x_new as usize, y_new as usize));
-            }
+            neighbors.push((x_new as usize, y_new as usize));
         }
     }
 
     neighbors
 }
 
-fn find_trailhead_scores(mountain: &Vec<Vec<usize>>, distinct_paths: bool) -> usize {
-    let mut trail_starts = Vec::new();
+fn find_trailhead_scores(mountain: &[Vec<Option<usize>>], distinct_paths: bool) -> usize {
+    let trails = Trails::new(mountain, HeightRules::default());
 
     let mut total_trail_scores = 0;
 
-    for (y_index, row) in mountain.iter().enumerate() {
-        for x in 0..row.len() {
-            if mountain[y_index][x] == 0 {
-                trail_starts.push((x, y_index));
-            }
-        }
-    }
-
-    for trail_start in trail_starts {
-        total_trail_scores += bfs(mountain, trail_start.0, trail_start.1, distinct_paths);
+    for (x, y) in trails.trailheads() {
+        total_trail_scores += if distinct_paths {
+            trails.rating(x, y)
+        } else {
+            trails.score(x, y)
+        };
     }
 
     total_trail_scores
 }
 
-fn bfs(mountain: &Vec<Vec<usize>>, x: usize, y: usize, distinct_paths: bool) -> usize {
-    let mut peaks_found = 0;
-
-    let mut nodes: VecDeque<(usize, usize)> = VecDeque::new();
-    let mut visited: HashSet<(usize, usize)> = HashSet::new();
-
-    nodes.push_back((x, y));
-
-    while !nodes.is_empty() {
-        let node = nodes.pop_front().unwrap();
-
-        if !distinct_paths {
-            if visited.contains(&node) {
-                continue
-            }
-
-            visited.insert(node);
-        }
-
-        if mountain[node.1][node.0] == 9 {
-            peaks_found += 1;
-            continue;
-        }
-
-        let neighbors = get_valid_neighbors(&mountain, node.0, node.1);
-
-        for neighbor in neighbors {
-            nodes.push_back(neighbor);
-        }
-    }
-
-    peaks_found
-}
-
 fn main() {
     let input = parse_to_array("day10part01.txt").unwrap();
     let mountain = parse_mountain(&input);
@@ -107,6 +103,8 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::heatmap::{render_ascii, render_ppm};
+    use crate::trails::TrailheadReport;
 
     #[test]
     fn dual_peak_test() {
@@ -177,4 +175,215 @@ mod tests {
 
         assert_eq!(find_trailhead_scores(&mountain, true), 81);
     }
+
+    #[test]
+    fn trail_paths_test() {
+        let input = vec![
+            ".....0.".to_string(),
+            "..4321.".to_string(),
+            "..5..2.".to_string(),
+            "..6543.".to_string(),
+            "..7..4.".to_string(),
+            "..8765.".to_string(),
+            "..9....".to_string(),
+        ];
+
+        let mountain = parse_mountain(&input);
+        let trails = Trails::new(&mountain, HeightRules::default());
+
+        assert_eq!(trails.trailheads(), vec![(5, 0)]);
+        assert_eq!(trails.score(5, 0), 1);
+        assert_eq!(trails.rating(5, 0), 3);
+        assert_eq!(trails.reachable_peaks(5, 0), vec![(2, 6)]);
+
+        let paths: Vec<Vec<(usize, usize)>> = trails.paths_from(5, 0).collect();
+        assert_eq!(paths.len(), 3);
+        for path in &paths {
+            assert_eq!(path.len(), 10);
+            assert_eq!(path.first(), Some(&(5, 0)));
+            assert_eq!(path.last(), Some(&(2, 6)));
+            for (height, &(x, y)) in path.iter().enumerate() {
+                assert_eq!(mountain[y][x], Some(height));
+            }
+        }
+        assert!(paths.contains(&vec![
+            (5, 0), (5, 1), (5, 2), (5, 3), (5, 4), (5, 5), (4, 5), (3, 5), (2, 5), (2, 6)
+        ]));
+
+        assert_eq!(trails.paths_from(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn paths_match_rating_test() {
+        let input = vec![
+            "89010123".to_string(),
+            "78121874".to_string(),
+            "87430965".to_string(),
+            "96549874".to_string(),
+            "45678903".to_string(),
+            "32019012".to_string(),
+            "01329801".to_string(),
+            "10456732".to_string(),
+        ];
+
+        let mountain = parse_mountain(&input);
+        let trails = Trails::new(&mountain, HeightRules::default());
+
+        for (x, y) in trails.trailheads() {
+            let paths: Vec<Vec<(usize, usize)>> = trails.paths_from(x, y).collect();
+            assert_eq!(paths.len(), trails.rating(x, y));
+
+            let peaks: std::collections::BTreeSet<(usize, usize)> =
+                paths.iter().map(|path| *path.last().unwrap()).collect();
+            assert_eq!(peaks.len(), trails.score(x, y));
+        }
+    }
+
+    #[test]
+    fn trailhead_report_test() {
+        let input = vec![
+            "..90..9".to_string(),
+            "...1.98".to_string(),
+            "...2..7".to_string(),
+            "6543456".to_string(),
+            "765.987".to_string(),
+            "876....".to_string(),
+            "987....".to_string(),
+        ];
+
+        let mountain = parse_mountain(&input);
+        let trails = Trails::new(&mountain, HeightRules::default());
+
+        assert_eq!(
+            trails.trailhead_reports(),
+            vec![TrailheadReport {
+                position: (3, 0),
+                score: 4,
+                rating: 13,
+                peaks: vec![(0, 6), (4, 4), (5, 1), (6, 0)],
+            }]
+        );
+    }
+
+    #[test]
+    fn heatmap_test() {
+        let input = vec![
+            "89010123".to_string(),
+            "78121874".to_string(),
+            "87430965".to_string(),
+            "96549874".to_string(),
+            "45678903".to_string(),
+            "32019012".to_string(),
+            "01329801".to_string(),
+            "10456732".to_string(),
+        ];
+
+        let mountain = parse_mountain(&input);
+        let trails = Trails::new(&mountain, HeightRules::default());
+        let heatmap = trails.heatmap();
+
+        let mut expected: Vec<Vec<usize>> = vec![vec![0; 8]; 8];
+        for (x, y) in trails.trailheads() {
+            for path in trails.paths_from(x, y) {
+                for (px, py) in path {
+                    expected[py][px] += 1;
+                }
+            }
+        }
+        assert_eq!(heatmap, expected);
+
+        let trailhead_total: usize = trails.trailheads().iter().map(|&(x, y)| heatmap[y][x]).sum();
+        assert_eq!(trailhead_total, 81);
+
+        let ascii = render_ascii(&heatmap);
+        assert_eq!(ascii.lines().count(), 8);
+        assert!(ascii.lines().all(|line| line.chars().count() == 8));
+        assert!(ascii.contains('@'));
+
+        let ppm = render_ppm(&mountain, &heatmap);
+        assert!(ppm.starts_with("P3\n8 8\n255\n"));
+        assert_eq!(ppm.lines().count(), 3 + 8);
+    }
+
+    #[test]
+    fn heatmap_render_test() {
+        let heatmap = vec![vec![0, 1, 2], vec![4, 8, 0]];
+        assert_eq!(render_ascii(&heatmap), " .:\n=@ \n");
+
+        let mountain = vec![vec![Some(0), Some(1), None], vec![Some(3), Some(4), Some(5)]];
+        assert_eq!(
+            render_ppm(&mountain, &heatmap),
+            "P3\n3 2\n255\n0 0 0 31 31 31 0 0 128\n127 127 127 255 255 255 0 0 0\n"
+        );
+    }
+
+    #[test]
+    fn elevation_rows_test() {
+        let input = vec![
+            "100,101,102".to_string(),
+            "#,,105".to_string(),
+            "110, 109 ,108".to_string(),
+        ];
+
+        let mountain = parse_elevation_rows(&input).unwrap();
+        assert_eq!(mountain[0], vec![Some(100), Some(101), Some(102)]);
+        assert_eq!(mountain[1], vec![None, None, Some(105)]);
+        assert_eq!(mountain[2], vec![Some(110), Some(109), Some(108)]);
+
+        assert!(parse_elevation_rows(&["1,x,3".to_string()]).is_err());
+    }
+
+    #[test]
+    fn generalised_height_rules_test() {
+        let input = vec![
+            "100,101,102".to_string(),
+            "#,,105".to_string(),
+            "110,109,108".to_string(),
+        ];
+
+        let mountain = parse_elevation_rows(&input).unwrap();
+
+        // the puzzle's rules cannot get past the jump from 102 to 105
+        let strict = HeightRules { start: 100, goal: 110, min_step: 1, max_step: 1 };
+        let trails = Trails::new(&mountain, strict);
+        assert_eq!(trails.score(0, 0), 0);
+
+        let climb_three = HeightRules { start: 100, goal: 110, min_step: 1, max_step: 3 };
+        let trails = Trails::new(&mountain, climb_three);
+        assert!(trails.is_acyclic());
+        assert_eq!(trails.trailheads(), vec![(0, 0)]);
+        assert_eq!(trails.score(0, 0), 1);
+        assert_eq!(trails.rating(0, 0), 1);
+        assert_eq!(
+            trails.paths_from(0, 0).next(),
+            Some(vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2)])
+        );
+    }
+
+    #[test]
+    fn descending_height_rules_test() {
+        let input = vec![
+            "0,1,2".to_string(),
+            "1,.,3".to_string(),
+            "2,3,5".to_string(),
+        ];
+
+        let mountain = parse_elevation_rows(&input).unwrap();
+
+        // climb up to two, descend any amount: steps can loop back, so trails are simple paths
+        let rules = HeightRules { start: 0, goal: 5, min_step: i64::MIN, max_step: 2 };
+        let trails = Trails::new(&mountain, rules);
+        assert!(!trails.is_acyclic());
+        assert_eq!(trails.score(0, 0), 1);
+        assert_eq!(trails.reachable_peaks(0, 0), vec![(2, 2)]);
+
+        let paths: Vec<Vec<(usize, usize)>> = trails.paths_from(0, 0).collect();
+        assert_eq!(paths.len(), trails.rating(0, 0));
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            let unique: std::collections::BTreeSet<&(usize, usize)> = path.iter().collect();
+            assert_eq!(unique.len(), path.len());
+            assert_eq!(path.last(), Some(&(2, 2)));
+        }
+    }
 }
@@ -0,0 +1,326 @@
+use crate::get_valid_neighbors;
+use std::collections::{BTreeSet, VecDeque};
+use std::rc::Rc;
+
+type PeakSet = Rc<BTreeSet<(usize, usize)>>;
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailheadReport {
+    pub position: (usize, usize),
+    pub score: usize,
+    pub rating: usize,
+    pub peaks: Vec<(usize, usize)>,
+}
+
+// Which heights trails start and end on, and which height changes a single step may make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeightRules {
+    pub start: usize,
+    pub goal: usize,
+    pub min_step: i64,
+    pub max_step: i64,
+}
+
+impl Default for HeightRules {
+    // the puzzle's rules, 0 to 9 climbing exactly one at a time
+    fn default() -> Self {
+        HeightRules {
+            start: 0,
+            goal: 9,
+            min_step: 1,
+            max_step: 1,
+        }
+    }
+}
+
+impl HeightRules {
+    pub fn allows(&self, from: usize, to: usize) -> bool {
+        let step = to as i64 - from as i64;
+        self.min_step <= step && step <= self.max_step
+    }
+}
+
+// Scores and ratings for every cell of the map. When the allowed steps cannot loop back on
+// themselves everything is worked out once from the goals down, otherwise trails are the
+// simple paths to a goal and ratings are counted by walking them
+pub struct Trails<'a> {
+    mountain: &'a [Vec<Option<usize>>],
+    rules: HeightRules,
+    can_reach_goal: Vec<Vec<bool>>,
+    ratings: Option<Vec<Vec<usize>>>,
+    peaks: Option<Vec<Vec<PeakSet>>>,
+}
+
+impl<'a> Trails<'a> {
+    pub fn new(mountain: &'a [Vec<Option<usize>>], rules: HeightRules) -> Self {
+        let mut trails = Trails {
+            mountain,
+            rules,
+            can_reach_goal: mountain.iter().map(|row| vec![false; row.len()]).collect(),
+            ratings: None,
+            peaks: None,
+        };
+
+        trails.mark_cells_reaching_goal();
+        if let Some(order) = trails.topological_order() {
+            trails.solve(&order);
+        }
+
+        trails
+    }
+
+    // trails end on the first goal they reach
+    fn steps(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        if self.mountain[y][x] == Some(self.rules.goal) {
+            return Vec::new();
+        }
+
+        get_valid_neighbors(self.mountain, &self.rules, x, y)
+    }
+
+    fn goal_steps(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut steps = self.steps(x, y);
+        steps.retain(|&(nx, ny)| self.can_reach_goal[ny][nx]);
+        steps
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.mountain
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| (x, y)))
+    }
+
+    fn mark_cells_reaching_goal(&mut self) {
+        let mut predecessors: Vec<Vec<Vec<(usize, usize)>>> =
+            self.mountain.iter().map(|row| vec![Vec::new(); row.len()]).collect();
+        let mut nodes: VecDeque<(usize, usize)> = VecDeque::new();
+
+        for (x, y) in self.cells() {
+            for (nx, ny) in self.steps(x, y) {
+                predecessors[ny][nx].push((x, y));
+            }
+
+            if self.mountain[y][x] == Some(self.rules.goal) {
+                self.can_reach_goal[y][x] = true;
+                nodes.push_back((x, y));
+            }
+        }
+
+        while let Some((x, y)) = nodes.pop_front() {
+            for &(px, py) in &predecessors[y][x] {
+                if !self.can_reach_goal[py][px] {
+                    self.can_reach_goal[py][px] = true;
+                    nodes.push_back((px, py));
+                }
+            }
+        }
+    }
+
+    // Kahn's algorithm over the cells that lead to a goal, None if their steps form a loop
+    fn topological_order(&self) -> Option<Vec<(usize, usize)>> {
+        let mut incoming: Vec<Vec<usize>> = self.mountain.iter().map(|row| vec![0; row.len()]).collect();
+        let relevant: Vec<(usize, usize)> = self
+            .cells()
+            .filter(|&(x, y)| self.can_reach_goal[y][x])
+            .collect();
+
+        for &(x, y) in &relevant {
+            for (nx, ny) in self.goal_steps(x, y) {
+                incoming[ny][nx] += 1;
+            }
+        }
+
+        let mut nodes: VecDeque<(usize, usize)> = relevant
+            .iter()
+            .copied()
+            .filter(|&(x, y)| incoming[y][x] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(relevant.len());
+
+        while let Some((x, y)) = nodes.pop_front() {
+            order.push((x, y));
+            for (nx, ny) in self.goal_steps(x, y) {
+                incoming[ny][nx] -= 1;
+                if incoming[ny][nx] == 0 {
+                    nodes.push_back((nx, ny));
+                }
+            }
+        }
+
+        (order.len() == relevant.len()).then_some(order)
+    }
+
+    // every step leads later in the order, so walking it backwards solves neighbors first
+    fn solve(&mut self, order: &[(usize, usize)]) {
+        let mut ratings: Vec<Vec<usize>> = self.mountain.iter().map(|row| vec![0; row.len()]).collect();
+        let mut peaks: Vec<Vec<PeakSet>> =
+            self.mountain.iter().map(|row| vec![Rc::default(); row.len()]).collect();
+
+        for &(x, y) in order.iter().rev() {
+            if self.mountain[y][x] == Some(self.rules.goal) {
+                ratings[y][x] = 1;
+                peaks[y][x] = Rc::new(BTreeSet::from([(x, y)]));
+                continue;
+            }
+
+            let neighbors = self.steps(x, y);
+            ratings[y][x] = neighbors.iter().map(|&(nx, ny)| ratings[ny][nx]).sum();
+            peaks[y][x] = match neighbors.as_slice() {
+                [] => Rc::default(),
+                [(nx, ny)] => Rc::clone(&peaks[*ny][*nx]),
+                _ => Rc::new(
+                    neighbors
+                        .iter()
+                        .flat_map(|&(nx, ny)| peaks[ny][nx].iter().copied())
+                        .collect(),
+                ),
+            };
+        }
+
+        self.ratings = Some(ratings);
+        self.peaks = Some(peaks);
+    }
+
+    pub fn is_acyclic(&self) -> bool {
+        self.ratings.is_some()
+    }
+
+    pub fn trailheads(&self) -> Vec<(usize, usize)> {
+        self.cells()
+            .filter(|&(x, y)| self.mountain[y][x] == Some(self.rules.start))
+            .collect()
+    }
+
+    // number of distinct goals reachable from the cell
+    pub fn score(&self, x: usize, y: usize) -> usize {
+        match &self.peaks {
+            Some(peaks) => peaks[y][x].len(),
+            None => self.reachable_peaks(x, y).len(),
+        }
+    }
+
+    // number of distinct trails from the cell to any goal
+    pub fn rating(&self, x: usize, y: usize) -> usize {
+        match &self.ratings {
+            Some(ratings) => ratings[y][x],
+            None => self.paths_from(x, y).count(),
+        }
+    }
+
+    pub fn reachable_peaks(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        if let Some(peaks) = &self.peaks {
+            return peaks[y][x].iter().copied().collect();
+        }
+
+        let mut visited: BTreeSet<(usize, usize)> = BTreeSet::from([(x, y)]);
+        let mut nodes: VecDeque<(usize, usize)> = VecDeque::from([(x, y)]);
+        let mut found: BTreeSet<(usize, usize)> = BTreeSet::new();
+
+        while let Some((x, y)) = nodes.pop_front() {
+            if self.mountain[y][x] == Some(self.rules.goal) {
+                found.insert((x, y));
+            }
+
+            for neighbor in self.steps(x, y) {
+                if visited.insert(neighbor) {
+                    nodes.push_back(neighbor);
+                }
+            }
+        }
+
+        found.into_iter().collect()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn trailhead_reports(&self) -> Vec<TrailheadReport> {
+        self.trailheads()
+            .into_iter()
+            .map(|(x, y)| TrailheadReport {
+                position: (x, y),
+                score: self.score(x, y),
+                rating: self.rating(x, y),
+                peaks: self.reachable_peaks(x, y),
+            })
+            .collect()
+    }
+
+    // how many distinct trails from any trailhead pass through each cell
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn heatmap(&self) -> Vec<Vec<usize>> {
+        let mut heatmap: Vec<Vec<usize>> = self.mountain.iter().map(|row| vec![0; row.len()]).collect();
+
+        let (Some(order), Some(ratings)) = (self.topological_order(), &self.ratings) else {
+            for (x, y) in self.trailheads() {
+                for path in self.paths_from(x, y) {
+                    for (px, py) in path {
+                        heatmap[py][px] += 1;
+                    }
+                }
+            }
+            return heatmap;
+        };
+
+        // trails through a cell = trails arriving at it times trails leaving it for a goal
+        let mut arriving: Vec<Vec<usize>> = self.mountain.iter().map(|row| vec![0; row.len()]).collect();
+        for &(x, y) in &order {
+            if self.mountain[y][x] == Some(self.rules.start) {
+                arriving[y][x] += 1;
+            }
+            for (nx, ny) in self.goal_steps(x, y) {
+                arriving[ny][nx] += arriving[y][x];
+            }
+            heatmap[y][x] = arriving[y][x] * ratings[y][x];
+        }
+
+        heatmap
+    }
+
+    // every trail from the cell to a goal, produced lazily so large ratings can be sampled
+    pub fn paths_from(&self, x: usize, y: usize) -> TrailPaths<'_, 'a> {
+        let mut stack = Vec::new();
+        if self.can_reach_goal[y][x] {
+            stack.push(((x, y), 0));
+        }
+
+        TrailPaths {
+            trails: self,
+            path: Vec::new(),
+            stack,
+        }
+    }
+}
+
+pub struct TrailPaths<'t, 'a> {
+    trails: &'t Trails<'a>,
+    path: Vec<(usize, usize)>,
+    // cells still to visit, each with the path length it extends
+    stack: Vec<((usize, usize), usize)>,
+}
+
+impl Iterator for TrailPaths<'_, '_> {
+    type Item = Vec<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let acyclic = self.trails.is_acyclic();
+
+        while let Some(((x, y), depth)) = self.stack.pop() {
+            self.path.truncate(depth);
+            self.path.push((x, y));
+
+            if self.trails.mountain[y][x] == Some(self.trails.rules.goal) {
+                return Some(self.path.clone());
+            }
+
+            let neighbors = self.trails.steps(x, y);
+            for &(nx, ny) in neighbors.iter().rev() {
+                if self.trails.can_reach_goal[ny][nx] && (acyclic || !self.path.contains(&(nx, ny))) {
+                    self.stack.push(((nx, ny), depth + 1));
+                }
+            }
+        }
+
+        None
+    }
+}
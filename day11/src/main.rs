@@ -1,8 +1,41 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::time::Instant;
+use common::big::BigUint;
+use common::digits::Digits;
 use common::parse_to_text;
+use crate::stone_rules::{MultiplyBy, RuleSet, ZeroBecomes};
 
-fn parse_stones(input: &String) -> Vec<i64> {
+mod stone_graph;
+mod stone_rules;
+
+#[derive(Debug, PartialEq, Eq)]
+enum StoneError {
+    ValueOverflow { value: u64, blink: usize },
+}
+
+impl fmt::Display for StoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoneError::ValueOverflow { value, blink } => {
+                write!(f, "stone {} overflows u64 on blink {}", value, blink)
+            }
+        }
+    }
+}
+
+impl Error for StoneError {}
+
+impl StoneError {
+    fn on_blink(self, blink: usize) -> Self {
+        match self {
+            StoneError::ValueOverflow { value, .. } => StoneError::ValueOverflow { value, blink },
+        }
+    }
+}
+
+fn parse_stones(input: &str) -> Vec<i64> {
     let mut stones: Vec<i64> = Vec::new();
 
     for stone in input.split(" ") {
@@ -15,50 +48,91 @@ fn parse_stones(input: &String) -> Vec<i64> {
 }
 
 fn even_length(value: &i64) -> bool {
-    let digits = ((*value as f64).log10().floor() as u32) + 1;
-    digits % 2 == 0
+    (*value as u64).digit_count().is_multiple_of(2)
 }
 
 fn split(value: &i64) -> (i64, i64) {
-    let digits = ((*value as f64).log10().floor() as u32) + 1;
-    let half = digits / 2;
+    let value = *value as u64;
+    let digits = value.digit_count();
+
+    // the second half takes the rounded down half of the digits
+    let (first_half, second_half) = value.split_at_digit(digits - digits / 2).unwrap();
+
+    (first_half as i64, second_half as i64)
+}
 
-    let divisor = 10_i64.pow(half);
-    let first_half = value / divisor;
-    let second_half = value % divisor;
+fn standard_rules() -> RuleSet {
+    RuleSet::new()
+        .rule(ZeroBecomes(1))
+        .rule(|value: i64| {
+            Ok(even_length(&value).then(|| {
+                let (first_half, second_half) = split(&value);
+                vec![first_half, second_half]
+            }))
+        })
+        .rule(MultiplyBy(2024))
+}
 
-    (first_half, second_half)
+#[cfg_attr(not(test), allow(dead_code))]
+fn blink(stones: &mut Vec<i64>) -> Result<(), StoneError> {
+    blink_with_rules(stones, &standard_rules(), 1)
 }
 
-fn blink(stones: &mut Vec<i64>) {
+// blink is only used to say where an overflow happened
+fn blink_with_rules(stones: &mut Vec<i64>, rules: &RuleSet, blink: usize) -> Result<(), StoneError> {
     let mut new_stones: Vec<i64> = Vec::with_capacity(stones.len() * 2);
-    for (_, stone_value) in stones.iter().enumerate() {
-        if *stone_value == 0 {
-            new_stones.push(1);
-        } else if even_length(stone_value) {
-            let (first_half, second_half) = split(stone_value);
-            new_stones.push(first_half);
-            new_stones.push(second_half);
-        } else {
-            new_stones.push(stone_value * 2024);
-        }
+    for stone_value in stones.iter() {
+        new_stones.extend(rules.apply(*stone_value, blink)?);
     }
 
     stones.clear();
     stones.extend(new_stones);
+
+    Ok(())
 }
 
-fn stones_after_n_blinks(stones: &mut Vec<i64>, count: usize) -> Vec<i64> {
-    for _ in 0..count {
-        blink(stones);
+fn stones_after_n_blinks(stones: &mut Vec<i64>, count: usize) -> Result<Vec<i64>, StoneError> {
+    stones_after_n_blinks_with_rules(stones, count, &standard_rules())
+}
+
+fn stones_after_n_blinks_with_rules(stones: &mut Vec<i64>, count: usize, rules: &RuleSet) -> Result<Vec<i64>, StoneError> {
+    for blink in 1..=count {
+        blink_with_rules(stones, rules, blink)?;
     }
 
-    stones.clone()
+    Ok(stones.clone())
+}
+
+fn stone_count_after_n_blinks(
+    stones: &[i64],
+    blinks: usize,
+    memo: &mut HashMap<(i64, usize), usize>,
+) -> Result<usize, StoneError> {
+    stone_count_after_n_blinks_with_rules(stones, blinks, &standard_rules(), memo)
+}
+
+// the memo is only valid for the rule set it was filled with
+fn stone_count_after_n_blinks_with_rules(
+    stones: &[i64],
+    blinks: usize,
+    rules: &RuleSet,
+    memo: &mut HashMap<(i64, usize), usize>,
+) -> Result<usize, StoneError> {
+    // the recursion counts blinks down, so overflows are reported with the blinks left and
+    // turned into the blink they happened on here
+    count_stones(stones, blinks, rules, memo).map_err(|error| match error {
+        StoneError::ValueOverflow { value, blink: left } => StoneError::ValueOverflow { value, blink: blinks - left + 1 },
+    })
 }
 
-fn stone_count_after_n_blinks(stones: &mut Vec<i64>, blinks: usize, memo: &mut HashMap<(i64, usize), usize>) -> usize {
+fn count_stones(
+    stones: &[i64],
+    blinks: usize,
+    rules: &RuleSet,
+    memo: &mut HashMap<(i64, usize), usize>,
+) -> Result<usize, StoneError> {
     if blinks == 0 {
-        return stones.len();
+        return Ok(stones.len());
     }
 
     let mut count = 0;
@@ -67,20 +141,69 @@ fn stone_count_after_n_blinks(stones: &mut Vec<i64>, blinks: usize, memo: &mut H
         if let Some(&cached_result) = memo.get(&(stone, blinks)) {
             count += cached_result;
         } else {
-            let mut single_stone = vec![stone];
-            blink(&mut single_stone);
-
-            let recursive_result =
-                single_stone.iter()
-                    .map(|s| stone_count_after_n_blinks(&mut vec![*s], blinks - 1, memo))
-                    .sum();
+            let mut recursive_result = 0;
+            for s in rules.apply(stone, blinks)? {
+                recursive_result += count_stones(&[s], blinks - 1, rules, memo)?;
+            }
 
             memo.insert((stone, blinks), recursive_result);
             count += recursive_result;
         }
     }
 
-    count
+    Ok(count)
+}
+
+// applies the blink rules to a single value, None if the value no longer fits in a u64
+fn blink_value(value: u64) -> Option<(u64, Option<u64>)> {
+    if value == 0 {
+        return Some((1, None));
+    }
+
+    let digits = value.digit_count();
+    if digits.is_multiple_of(2) {
+        let (first_half, second_half) = value.split_at_digit(digits / 2)?;
+        Some((first_half, Some(second_half)))
+    } else {
+        value.checked_mul(2024).map(|product| (product, None))
+    }
+}
+
+fn add_stones(counts: &mut HashMap<u64, BigUint>, value: u64, count: &BigUint) {
+    let entry = counts.entry(value).or_default();
+    *entry = entry.add(count);
+}
+
+// tracks how many stones carry each value, index n of the result is the count after n blinks.
+// values stay small enough to run thousands of blinks, and the counts, which outgrow u128
+// after ~200, are never bounded
+fn stone_counts_per_blink(stones: &[u64], blinks: usize) -> Result<Vec<BigUint>, StoneError> {
+    let mut counts: HashMap<u64, BigUint> = HashMap::new();
+    for &stone in stones {
+        add_stones(&mut counts, stone, &BigUint::from(1_u64));
+    }
+
+    let mut totals: Vec<BigUint> = Vec::with_capacity(blinks + 1);
+    totals.push(BigUint::from(stones.len() as u64));
+
+    for blink in 1..=blinks {
+        let mut next_counts: HashMap<u64, BigUint> = HashMap::with_capacity(counts.len());
+
+        for (&value, count) in &counts {
+            let (first, second) = blink_value(value).ok_or(StoneError::ValueOverflow { value, blink })?;
+            add_stones(&mut next_counts, first, count);
+            if let Some(second) = second {
+                add_stones(&mut next_counts, second, count);
+            }
+        }
+
+        let total = next_counts.values().fold(BigUint::default(), |total, count| total.add(count));
+
+        totals.push(total);
+        counts = next_counts;
+    }
+
+    Ok(totals)
 }
 
 fn main() {
@@ -88,7 +211,7 @@ fn main() {
     let stones = &mut parse_stones(&input);
 
     let start_part_one = Instant::now();
-    let part_one = stones_after_n_blinks(stones, 25).len();
+    let part_one = stones_after_n_blinks(stones, 25).unwrap().len();
     let duration_part_one = start_part_one.elapsed();
     println!("{}", part_one);
     println!("Part 1 completed in: {:?}", duration_part_one);
@@ -96,15 +219,25 @@ fn main() {
     let stones = &mut parse_stones(&input);
     let memo = &mut HashMap::new();
     let start_part_two = Instant::now();
-    let part_two = stone_count_after_n_blinks(stones, 75, memo);
+    let part_two = stone_count_after_n_blinks(stones, 75, memo).unwrap();
     let duration_part_two = start_part_two.elapsed();
     println!("{}", part_two);
     println!("Part 2 completed in: {:?}", duration_part_two);
+
+    let stones: Vec<u64> = parse_stones(&input).iter().map(|&stone| stone as u64).collect();
+    let start_count_map = Instant::now();
+    let count_map = &stone_counts_per_blink(&stones, 75).unwrap()[75];
+    let duration_count_map = start_count_map.elapsed();
+    println!("{}", count_map);
+    println!("Part 2 (count map) completed in: {:?}", duration_count_map);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stone_graph::StoneGraph;
+    use crate::stone_rules::{DigitSum, SplitDigits, StoneRule};
+    use std::collections::HashSet;
 
     #[test]
     fn split_test() {
@@ -112,12 +245,27 @@ mod tests {
         assert_eq!(split(&1000), (10, 0));
         assert_eq!(split(&1234), (12, 34));
         assert_eq!(split(&123456), (123, 456));
+        assert_eq!(split(&100_000_000_000_000_000), (100_000_000, 0));
+        assert_eq!(split(&9_999_999_999_999_999), (99_999_999, 99_999_999));
+    }
+
+    #[test]
+    fn even_length_test() {
+        assert!(!even_length(&0));
+        assert!(!even_length(&9));
+        assert!(even_length(&10));
+        assert!(even_length(&99));
+        assert!(!even_length(&100));
+        assert!(!even_length(&999_999_999_999_999));
+        assert!(even_length(&1_000_000_000_000_000));
+        assert!(even_length(&999_999_999_999_999_999));
+        assert!(!even_length(&1_000_000_000_000_000_000));
     }
 
     #[test]
     fn blink_test() {
         let input = &mut vec![125, 17];
-        blink(input);
+        blink(input).unwrap();
 
         assert_eq!(input.clone(), vec![253000, 1, 7])
     }
@@ -125,21 +273,153 @@ mod tests {
     #[test]
     fn part_one_test() {
         let input = &mut vec![125, 17];
-        assert_eq!(stones_after_n_blinks(input, 6), vec![2097446912,14168,4048,2,0,2,4,40,48,2024,40,48,80,96,2,8,6,7,6,0,3,2]);
+        assert_eq!(stones_after_n_blinks(input, 6).unwrap(), vec![2097446912,14168,4048,2,0,2,4,40,48,2024,40,48,80,96,2,8,6,7,6,0,3,2]);
 
         let input = &mut vec![125, 17];
-        assert_eq!(stones_after_n_blinks(input, 6).len(), 22);
+        assert_eq!(stones_after_n_blinks(input, 6).unwrap().len(), 22);
 
         let input = &mut vec![125, 17];
-        assert_eq!(stones_after_n_blinks(input, 25).len(), 55312);
+        assert_eq!(stones_after_n_blinks(input, 25).unwrap().len(), 55312);
     }
 
     #[test]
     fn part_two_test() {
-        let input = &mut vec![125, 17];
-        assert_eq!(stone_count_after_n_blinks(input, 6, &mut HashMap::new()), 22);
+        let input = &[125, 17];
+        assert_eq!(stone_count_after_n_blinks(input, 6, &mut HashMap::new()).unwrap(), 22);
 
-        let input = &mut vec![125, 17];
-        assert_eq!(stone_count_after_n_blinks(input, 25, &mut HashMap::new()), 55312);
+        let input = &[125, 17];
+        assert_eq!(stone_count_after_n_blinks(input, 25, &mut HashMap::new()).unwrap(), 55312);
+    }
+
+    #[test]
+    fn rule_variants_test() {
+        let times_ten = RuleSet::new().rule(ZeroBecomes(1)).rule(SplitDigits(2)).rule(MultiplyBy(10));
+        let input = &mut vec![0, 5, 1234];
+        blink_with_rules(input, &times_ten, 1).unwrap();
+        assert_eq!(input.clone(), vec![1, 50, 12, 34]);
+
+        let thirds = RuleSet::new().rule(SplitDigits(3)).rule(SplitDigits(2)).rule(MultiplyBy(2024));
+        let input = &mut vec![123456, 100200, 1234, 7];
+        blink_with_rules(input, &thirds, 1).unwrap();
+        assert_eq!(input.clone(), vec![12, 34, 56, 10, 2, 0, 12, 34, 14168]);
+
+        let digit_sum = RuleSet::new().rule(DigitSum(3)).rule(MultiplyBy(3));
+        let input = &mut vec![999, 12, 4096];
+        blink_with_rules(input, &digit_sum, 1).unwrap();
+        assert_eq!(input.clone(), vec![27, 36, 19]);
+
+        let unmatched = RuleSet::new().rule(ZeroBecomes(7));
+        let input = &mut vec![0, 3];
+        blink_with_rules(input, &unmatched, 1).unwrap();
+        assert_eq!(input.clone(), vec![7, 3]);
+    }
+
+    #[test]
+    fn rule_engines_agree_test() {
+        let rule_sets = [
+            standard_rules(),
+            RuleSet::new().rule(ZeroBecomes(1)).rule(SplitDigits(2)).rule(MultiplyBy(1000)),
+            RuleSet::new().rule(ZeroBecomes(1)).rule(SplitDigits(3)).rule(SplitDigits(2)).rule(MultiplyBy(2024)),
+            RuleSet::new().rule(ZeroBecomes(1)).rule(DigitSum(4)).rule(MultiplyBy(7)),
+        ];
+
+        for rules in &rule_sets {
+            let listed = stones_after_n_blinks_with_rules(&mut vec![125, 17], 15, rules).unwrap().len();
+            let counted = stone_count_after_n_blinks_with_rules(&[125, 17], 15, rules, &mut HashMap::new()).unwrap();
+            assert_eq!(listed, counted);
+        }
+    }
+
+    #[test]
+    fn rule_overflow_test() {
+        let rules = RuleSet::new().rule(ZeroBecomes(1)).rule(MultiplyBy(1000));
+        let overflow = StoneError::ValueOverflow { value: 1_000_000_000_000_000_000, blink: 8 };
+
+        // 1, 1000, ... 10^18 still fits in an i64 and 10^21 does not, on the eighth blink
+        assert_eq!(stones_after_n_blinks_with_rules(&mut vec![0], 5, &rules).unwrap(), vec![1_000_000_000_000]);
+        assert_eq!(stones_after_n_blinks_with_rules(&mut vec![0], 10, &rules).unwrap_err(), overflow);
+        assert_eq!(
+            stone_count_after_n_blinks_with_rules(&[0], 10, &rules, &mut HashMap::new()).unwrap_err(),
+            overflow
+        );
+        assert_eq!(
+            MultiplyBy(2024).apply(i64::MAX / 1000),
+            Err(StoneError::ValueOverflow { value: i64::MAX as u64 / 1000, blink: 0 })
+        );
+    }
+
+    #[test]
+    fn stone_graph_test() {
+        let graph = StoneGraph::explore(&[0], None).unwrap();
+
+        let cycles = graph.cycles();
+        let zero_cycle = cycles.iter().find(|cycle| cycle.contains(&0)).unwrap();
+        for value in [1, 2, 4, 20, 24, 2024] {
+            assert!(zero_cycle.contains(&value));
+        }
+
+        let stats = graph.value_stats();
+        let zero = stats.iter().find(|stats| stats.value == 0).unwrap();
+        assert_eq!(zero.first_seen, 0);
+        assert_eq!(zero.children, vec![1]);
+        assert!(zero.in_cycle);
+        let twenty_four = stats.iter().find(|stats| stats.value == 2024).unwrap();
+        assert_eq!(twenty_four.first_seen, 2);
+        assert_eq!(twenty_four.children, vec![20, 24]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph stones {"));
+        assert!(dot.contains("    0 [style=bold];"));
+        assert!(dot.contains("    2024 -> 20;"));
+    }
+
+    #[test]
+    fn stone_graph_covers_simulation_test() {
+        let graph = StoneGraph::explore(&[125, 17], None).unwrap();
+        assert_eq!(graph.transitions.len(), graph.distinct_values());
+
+        let mut seen: HashSet<i64> = HashSet::new();
+        let stones = &mut vec![125, 17];
+        for blinks in 0..=25 {
+            for &stone in stones.iter() {
+                assert!(graph.first_seen[&stone] <= blinks);
+                seen.insert(stone);
+            }
+            blink(stones).unwrap();
+        }
+        assert!(seen.len() <= graph.distinct_values());
+
+        let limited = StoneGraph::explore(&[125, 17], Some(3)).unwrap();
+        assert_eq!(limited.first_seen.values().max(), Some(&3));
+        assert!(limited.to_dot().contains("[style=dashed]"));
+    }
+
+    #[test]
+    fn count_map_test() {
+        let counts = stone_counts_per_blink(&[125, 17], 25).unwrap();
+
+        let first: Vec<String> = counts[..7].iter().map(|count| count.to_string()).collect();
+        assert_eq!(first, ["2", "3", "4", "5", "9", "13", "22"]);
+        assert_eq!(counts[25], BigUint::from(55312_u64));
+
+        let memo = &mut HashMap::new();
+        for blinks in [40, 75] {
+            let expected = stone_count_after_n_blinks(&[125, 17], blinks, memo).unwrap() as u64;
+            assert_eq!(stone_counts_per_blink(&[125, 17], blinks).unwrap()[blinks], BigUint::from(expected));
+        }
+    }
+
+    #[test]
+    fn count_map_long_run_test() {
+        assert_eq!(
+            stone_counts_per_blink(&[9_999_999_999_999_999_999], 1),
+            Err(StoneError::ValueOverflow { value: 9_999_999_999_999_999_999, blink: 1 })
+        );
+
+        // far past the ~200 blinks a u128 count would last
+        let counts = stone_counts_per_blink(&[125, 17], 1000).unwrap();
+        assert_eq!(counts.len(), 1001);
+        assert!(counts[1000].digit_count() > u128::MAX.to_string().len() as u32);
+        assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]));
     }
 }
@@ -0,0 +1,200 @@
+use crate::{blink, StoneError};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueStats {
+    pub value: i64,
+    pub first_seen: usize,
+    pub children: Vec<i64>,
+    pub parent_count: usize,
+    pub in_cycle: bool,
+}
+
+// Every distinct stone value reachable from the starting stones and what each one blinks into
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct StoneGraph {
+    pub transitions: BTreeMap<i64, Vec<i64>>,
+    pub first_seen: BTreeMap<i64, usize>,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl StoneGraph {
+    // explores until no new values appear, or until max_blinks if given
+    pub fn explore(stones: &[i64], max_blinks: Option<usize>) -> Result<Self, StoneError> {
+        let mut transitions: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        let mut first_seen: BTreeMap<i64, usize> = BTreeMap::new();
+
+        let mut frontier: BTreeSet<i64> = stones.iter().copied().collect();
+        for &value in &frontier {
+            first_seen.insert(value, 0);
+        }
+
+        let mut blinks = 0;
+        while !frontier.is_empty() && max_blinks.is_none_or(|max| blinks < max) {
+            blinks += 1;
+            let mut next_frontier: BTreeSet<i64> = BTreeSet::new();
+
+            for value in frontier {
+                let mut children = vec![value];
+                blink(&mut children).map_err(|error| error.on_blink(blinks))?;
+
+                for &child in &children {
+                    if let Entry::Vacant(entry) = first_seen.entry(child) {
+                        entry.insert(blinks);
+                        next_frontier.insert(child);
+                    }
+                }
+
+                transitions.insert(value, children);
+            }
+
+            frontier = next_frontier;
+        }
+
+        Ok(StoneGraph {
+            transitions,
+            first_seen,
+        })
+    }
+
+    pub fn distinct_values(&self) -> usize {
+        self.first_seen.len()
+    }
+
+    // values that can reach themselves again, grouped by strongly connected component
+    pub fn cycles(&self) -> Vec<Vec<i64>> {
+        let mut tarjan = Tarjan::default();
+        for &value in self.transitions.keys() {
+            if !tarjan.index.contains_key(&value) {
+                tarjan.visit(value, &self.transitions);
+            }
+        }
+
+        tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self.transitions
+                        .get(&component[0])
+                        .is_some_and(|children| children.contains(&component[0]))
+            })
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect()
+    }
+
+    pub fn value_stats(&self) -> Vec<ValueStats> {
+        let mut parent_counts: BTreeMap<i64, usize> = BTreeMap::new();
+        for children in self.transitions.values() {
+            for &child in children {
+                *parent_counts.entry(child).or_default() += 1;
+            }
+        }
+
+        let in_cycle: BTreeSet<i64> = self.cycles().into_iter().flatten().collect();
+
+        self.first_seen
+            .iter()
+            .map(|(&value, &first_seen)| ValueStats {
+                value,
+                first_seen,
+                children: self.transitions.get(&value).cloned().unwrap_or_default(),
+                parent_count: parent_counts.get(&value).copied().unwrap_or(0),
+                in_cycle: in_cycle.contains(&value),
+            })
+            .collect()
+    }
+
+    // cycle members are drawn bold, values left unexplored by a blink limit are dashed
+    pub fn to_dot(&self) -> String {
+        let in_cycle: BTreeSet<i64> = self.cycles().into_iter().flatten().collect();
+        let mut dot = String::from("digraph stones {\n");
+
+        for &value in self.first_seen.keys() {
+            let style = if in_cycle.contains(&value) {
+                " [style=bold]"
+            } else if !self.transitions.contains_key(&value) {
+                " [style=dashed]"
+            } else {
+                ""
+            };
+            writeln!(dot, "    {}{};", value, style).unwrap();
+        }
+
+        for (value, children) in &self.transitions {
+            for child in children {
+                writeln!(dot, "    {} -> {};", value, child).unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// iterative to keep deep value chains off the call stack
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Default)]
+struct Tarjan {
+    index: BTreeMap<i64, usize>,
+    low_link: BTreeMap<i64, usize>,
+    stack: Vec<i64>,
+    on_stack: BTreeSet<i64>,
+    components: Vec<Vec<i64>>,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl Tarjan {
+    fn visit(&mut self, root: i64, transitions: &BTreeMap<i64, Vec<i64>>) {
+        let mut work: Vec<(i64, usize)> = vec![(root, 0)];
+        self.open(root);
+
+        while let Some((value, child_index)) = work.pop() {
+            let children = transitions.get(&value).map(|c| c.as_slice()).unwrap_or(&[]);
+
+            if let Some(&child) = children.get(child_index) {
+                work.push((value, child_index + 1));
+
+                if !self.index.contains_key(&child) {
+                    self.open(child);
+                    work.push((child, 0));
+                } else if self.on_stack.contains(&child) {
+                    let low = self.low_link[&value].min(self.index[&child]);
+                    self.low_link.insert(value, low);
+                }
+                continue;
+            }
+
+            if let Some(&(parent, _)) = work.last() {
+                let low = self.low_link[&parent].min(self.low_link[&value]);
+                self.low_link.insert(parent, low);
+            }
+
+            if self.low_link[&value] == self.index[&value] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    component.push(member);
+                    if member == value {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn open(&mut self, value: i64) {
+        let next_index = self.index.len();
+        self.index.insert(value, next_index);
+        self.low_link.insert(value, next_index);
+        self.stack.push(value);
+        self.on_stack.insert(value);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::StoneError;
+use common::digits::Digits;
+
+// Rules do not know which blink they run on, so they report overflow on blink 0 and the
+// engine running them fills in the real one
+pub trait StoneRule {
+    // the stones replacing value, or None if the rule does not apply to it
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError>;
+}
+
+impl<F> StoneRule for F
+where
+    F: Fn(i64) -> Result<Option<Vec<i64>>, StoneError>,
+{
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError> {
+        self(value)
+    }
+}
+
+// replaces a zero stone with the given value
+pub struct ZeroBecomes(pub i64);
+
+impl StoneRule for ZeroBecomes {
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError> {
+        Ok((value == 0).then(|| vec![self.0]))
+    }
+}
+
+// splits stones whose digit count is a multiple of the part count into equal length parts
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct SplitDigits(pub u32);
+
+impl StoneRule for SplitDigits {
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError> {
+        let digits = digit_count(value);
+        if self.0 < 2 || !digits.is_multiple_of(self.0) {
+            return Ok(None);
+        }
+
+        let divisor = 10_i64.pow(digits / self.0);
+        let mut parts = Vec::with_capacity(self.0 as usize);
+        let mut remaining = value;
+        for _ in 0..self.0 {
+            parts.push(remaining % divisor);
+            remaining /= divisor;
+        }
+        parts.reverse();
+
+        Ok(Some(parts))
+    }
+}
+
+pub struct MultiplyBy(pub i64);
+
+impl StoneRule for MultiplyBy {
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError> {
+        let product = value.checked_mul(self.0).ok_or(StoneError::ValueOverflow {
+            value: value.unsigned_abs(),
+            blink: 0,
+        })?;
+
+        Ok(Some(vec![product]))
+    }
+}
+
+// replaces a stone with the sum of its digits, only for stones with at least the given digit count
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct DigitSum(pub u32);
+
+impl StoneRule for DigitSum {
+    fn apply(&self, value: i64) -> Result<Option<Vec<i64>>, StoneError> {
+        if digit_count(value) < self.0 {
+            return Ok(None);
+        }
+
+        let mut sum = 0;
+        let mut remaining = value;
+        while remaining > 0 {
+            sum += remaining % 10;
+            remaining /= 10;
+        }
+
+        Ok(Some(vec![sum]))
+    }
+}
+
+// rules are tried in the order they were added, the first one that applies wins.
+// a stone no rule applies to is left unchanged
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<Box<dyn StoneRule>>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        RuleSet { rules: Vec::new() }
+    }
+
+    pub fn rule(mut self, rule: impl StoneRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn apply(&self, value: i64, blink: usize) -> Result<Vec<i64>, StoneError> {
+        for rule in &self.rules {
+            if let Some(stones) = rule.apply(value).map_err(|error| error.on_blink(blink))? {
+                return Ok(stones);
+            }
+        }
+
+        Ok(vec![value])
+    }
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+fn digit_count(value: i64) -> u32 {
+    value.unsigned_abs().digit_count()
+}
@@ -0,0 +1,221 @@
+use crate::Connectivity;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct RegionStats {
+    pub plant: char,
+    pub cells: Vec<(usize, usize)>,
+    pub perimeter: usize,
+    pub sides: usize,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl RegionStats {
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn price(&self) -> usize {
+        self.area() * self.perimeter
+    }
+
+    pub fn price_by_face(&self) -> usize {
+        self.area() * self.sides
+    }
+}
+
+// Garden that keeps its regions and fence prices up to date as single plots are changed.
+// Regions are joined with the given connectivity, fences always run along orthogonal edges
+// like fence_price
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct EditableGarden {
+    plots: Vec<Vec<char>>,
+    connectivity: Connectivity,
+    region_ids: Vec<Vec<usize>>,
+    regions: HashMap<usize, RegionStats>,
+    next_id: usize,
+    total_price: usize,
+    total_price_by_face: usize,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl EditableGarden {
+    pub fn new(garden: Vec<Vec<char>>) -> Self {
+        EditableGarden::with_connectivity(garden, Connectivity::Four)
+    }
+
+    pub fn with_connectivity(garden: Vec<Vec<char>>, connectivity: Connectivity) -> Self {
+        let height = garden.len();
+        let width = garden.first().map_or(0, |row| row.len());
+
+        let mut editable = EditableGarden {
+            plots: garden,
+            connectivity,
+            region_ids: vec![vec![usize::MAX; width]; height],
+            regions: HashMap::new(),
+            next_id: 0,
+            total_price: 0,
+            total_price_by_face: 0,
+        };
+
+        let all_cells: Vec<(usize, usize)> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .collect();
+        editable.rebuild_regions(&all_cells);
+
+        editable
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.plots.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    // in row-major order of their first cell, like parse_garden_regions
+    pub fn regions(&self) -> impl Iterator<Item = &RegionStats> {
+        let mut regions: Vec<&RegionStats> = self.regions.values().collect();
+        regions.sort_by_key(|region| (region.cells[0].1, region.cells[0].0));
+        regions.into_iter()
+    }
+
+    pub fn region_at(&self, x: usize, y: usize) -> Option<&RegionStats> {
+        let id = self.region_ids.get(y)?.get(x)?;
+        self.regions.get(id)
+    }
+
+    pub fn total_price(&self) -> usize {
+        self.total_price
+    }
+
+    pub fn total_price_by_face(&self) -> usize {
+        self.total_price_by_face
+    }
+
+    // Changes one plot and reprices only the regions touching it. Returns false when out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, plant: char) -> bool {
+        match self.get(x, y) {
+            None => return false,
+            Some(current) if current == plant => return true,
+            Some(_) => {}
+        }
+
+        // The edited cell's region may split, its neighbors' regions may merge,
+        // and every region bordering the cell gains or loses fence
+        let mut affected_ids: HashSet<usize> = HashSet::new();
+        affected_ids.insert(self.region_ids[y][x]);
+        for (nx, ny) in self.in_bounds_neighbors(x, y, self.connectivity.directions()) {
+            affected_ids.insert(self.region_ids[ny][nx]);
+        }
+
+        let mut affected_cells: Vec<(usize, usize)> = Vec::new();
+        for id in affected_ids {
+            if let Some(region) = self.regions.remove(&id) {
+                self.total_price -= region.price();
+                self.total_price_by_face -= region.price_by_face();
+                affected_cells.extend(region.cells);
+            }
+        }
+
+        self.plots[y][x] = plant;
+        for &(cx, cy) in &affected_cells {
+            self.region_ids[cy][cx] = usize::MAX;
+        }
+
+        self.rebuild_regions(&affected_cells);
+
+        true
+    }
+
+    fn rebuild_regions(&mut self, cells: &[(usize, usize)]) {
+        for &first in cells {
+            if self.region_ids[first.1][first.0] != usize::MAX {
+                continue;
+            }
+
+            let id = self.next_id;
+            self.next_id += 1;
+
+            let plant = self.plots[first.1][first.0];
+            let mut nodes = vec![first];
+            let mut region: Vec<(usize, usize)> = Vec::new();
+            self.region_ids[first.1][first.0] = id;
+
+            while let Some(node) = nodes.pop() {
+                region.push(node);
+
+                for neighbor in self.same_plant_neighbors(node.0, node.1, self.connectivity) {
+                    if self.region_ids[neighbor.1][neighbor.0] != id {
+                        self.region_ids[neighbor.1][neighbor.0] = id;
+                        nodes.push(neighbor);
+                    }
+                }
+            }
+            region.sort_by_key(|&(x, y)| (y, x));
+
+            let perimeter = region
+                .iter()
+                .map(|&(x, y)| 4 - self.same_plant_neighbors(x, y, Connectivity::Four).len())
+                .sum();
+            let sides = region.iter().map(|&(x, y)| self.corner_count(x, y)).sum();
+
+            let stats = RegionStats {
+                plant,
+                cells: region,
+                perimeter,
+                sides,
+            };
+
+            self.total_price += stats.price();
+            self.total_price_by_face += stats.price_by_face();
+            self.regions.insert(id, stats);
+        }
+    }
+
+    fn in_bounds_neighbors(&self, x: usize, y: usize, directions: &[(i32, i32)]) -> Vec<(usize, usize)> {
+        directions
+            .iter()
+            .filter_map(|&(dx, dy)| self.offset(x, y, dx, dy))
+            .collect()
+    }
+
+    fn same_plant_neighbors(&self, x: usize, y: usize, connectivity: Connectivity) -> Vec<(usize, usize)> {
+        let plant = self.plots[y][x];
+        self.in_bounds_neighbors(x, y, connectivity.directions())
+            .into_iter()
+            .filter(|&(nx, ny)| self.plots[ny][nx] == plant)
+            .collect()
+    }
+
+    fn offset(&self, x: usize, y: usize, dx: i32, dy: i32) -> Option<(usize, usize)> {
+        let x_new = x as i32 + dx;
+        let y_new = y as i32 + dy;
+        if x_new < 0 || y_new < 0 {
+            return None;
+        }
+
+        let (x_new, y_new) = (x_new as usize, y_new as usize);
+        self.get(x_new, y_new).map(|_| (x_new, y_new))
+    }
+
+    fn same_plant_at(&self, x: usize, y: usize, dx: i32, dy: i32) -> bool {
+        self.offset(x, y, dx, dy)
+            .is_some_and(|(nx, ny)| self.plots[ny][nx] == self.plots[y][x])
+    }
+
+    // Same corner rules as get_region_corner_count, checked against the grid instead of a cell list
+    fn corner_count(&self, x: usize, y: usize) -> usize {
+        let mut corners = 0;
+
+        for (dx, dy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            let horizontal = self.same_plant_at(x, y, dx, 0);
+            let vertical = self.same_plant_at(x, y, 0, dy);
+            let diagonal = self.same_plant_at(x, y, dx, dy);
+
+            if (!horizontal && !vertical) || (horizontal && vertical && !diagonal) {
+                corners += 1;
+            }
+        }
+
+        corners
+    }
+}
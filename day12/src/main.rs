@@ -1,8 +1,9 @@
 use common::parse_to_array;
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use std::collections::HashSet;
 
-fn parse_garden(input: &Vec<String>) -> Vec<Vec<char>> {
+mod garden_editor;
+
+fn parse_garden(input: &[String]) -> Vec<Vec<char>> {
     let mut garden: Vec<Vec<char>> = Vec::new();
 
     for line in input {
@@ -16,14 +17,43 @@ fn parse_garden(input: &Vec<String>) -> Vec<Vec<char>> {
     garden
 }
 
-fn get_neighbors(garden: &Vec<Vec<char>>, x: usize, y: usize) -> Vec<(usize, usize)> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connectivity {
+    Four,
+    #[cfg_attr(not(test), allow(dead_code))]
+    Eight,
+}
+
+impl Connectivity {
+    fn directions(&self) -> &'static [(i32, i32)] {
+        match self {
+            Connectivity::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            Connectivity::Eight => &[
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (-1, 1),
+                (1, -1),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
+fn get_neighbors(
+    garden: &[Vec<char>],
+    x: usize,
+    y: usize,
+    connectivity: Connectivity,
+) -> Vec<(usize, usize)> {
     let mut neighbors = Vec::new();
     let char = garden[y][x];
     let max_x = garden.first().unwrap().len();
     let max_y = garden.len();
 
-    let directions = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
-    for (dx, dy) in directions {
+    for &(dx, dy) in connectivity.directions() {
         let x_new: i32 = x as i32 + dx;
         let y_new: i32 = y as i32 + dy;
         if x_new >= 0
@@ -39,9 +69,13 @@ fn get_neighbors(garden: &Vec<Vec<char>>, x: usize, y: usize) -> Vec<(usize, usi
     neighbors
 }
 
-fn parse_garden_regions(garden: &Vec<Vec<char>>) -> HashMap<(usize, usize), Vec<(usize, usize)>> {
+// regions are returned in row-major order of their first cell
+fn parse_garden_regions(
+    garden: &[Vec<char>],
+    connectivity: Connectivity,
+) -> Vec<Vec<(usize, usize)>> {
     let mut visited: HashSet<(usize, usize)> = HashSet::new();
-    let mut regions: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    let mut regions: Vec<Vec<(usize, usize)>> = Vec::new();
 
     for y in 0..garden.len() {
         for x in 0..garden.first().unwrap().len() {
@@ -61,31 +95,29 @@ fn parse_garden_regions(garden: &Vec<Vec<char>>) -> HashMap<(usize, usize), Vec<
                 visited.insert(node);
                 region.push(node);
 
-                let neighbors = get_neighbors(garden, node.0, node.1);
+                let neighbors = get_neighbors(garden, node.0, node.1, connectivity);
                 for neighbor in neighbors {
                     nodes.push(neighbor);
                 }
             }
 
-            regions.insert(first, region);
+            regions.push(region);
         }
     }
 
     regions
 }
 
-fn fence_price(
-    garden: &Vec<Vec<char>>,
-    regions: &HashMap<(usize, usize), Vec<(usize, usize)>>,
-) -> usize {
+// fences run along cell edges, so only orthogonal neighbors share one regardless of connectivity
+fn fence_price(garden: &[Vec<char>], regions: &[Vec<(usize, usize)>]) -> usize {
     let mut fence_price = 0;
 
-    for (_, contents) in regions {
+    for contents in regions {
         let mut area = 0;
         let mut perimeter = 0;
         for cell in contents {
             area += 1;
-            let neighbor_count = get_neighbors(garden, cell.0, cell.1).len();
+            let neighbor_count = get_neighbors(garden, cell.0, cell.1, Connectivity::Four).len();
             perimeter += 4 - neighbor_count;
         }
 
@@ -95,7 +127,7 @@ fn fence_price(
     fence_price
 }
 
-fn get_outside_corner_count(region: &Vec<(usize, usize)>) -> usize {
+fn get_outside_corner_count(region: &[(usize, usize)]) -> usize {
     let mut outside_corner_count = 0;
 
     for &(x, y) in region {
@@ -127,7 +159,7 @@ fn get_outside_corner_count(region: &Vec<(usize, usize)>) -> usize {
     outside_corner_count
 }
 
-fn get_inside_corner_count(region: &Vec<(usize, usize)>) -> usize {
+fn get_inside_corner_count(region: &[(usize, usize)]) -> usize {
     let mut inside_corner_count = 0;
 
     for &(x, y) in region {
@@ -171,14 +203,16 @@ fn get_inside_corner_count(region: &Vec<(usize, usize)>) -> usize {
     inside_corner_count
 }
 
-fn get_region_corner_count(region: &Vec<(usize, usize)>) -> usize {
+fn get_region_corner_count(region: &[(usize, usize)]) -> usize {
     get_inside_corner_count(region) + get_outside_corner_count(region)
 }
 
-fn fence_price_by_face(regions: &HashMap<(usize, usize), Vec<(usize, usize)>>) -> usize {
+// corners are counted against region membership, so diagonally joined cells of an
+// 8-connected region each keep their own corner where they touch
+fn fence_price_by_face(regions: &[Vec<(usize, usize)>]) -> usize {
     let mut total = 0;
 
-    for (_, region) in regions {
+    for region in regions {
         total += get_region_corner_count(region) * region.len();
     }
 
@@ -188,7 +222,7 @@ fn fence_price_by_face(regions: &HashMap<(usize, usize), Vec<(usize, usize)>>) -
 fn main() {
     let input = parse_to_array("day12part01.txt").unwrap();
     let garden = parse_garden(&input);
-    let garden_regions = parse_garden_regions(&garden);
+    let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
 
     let part_one = fence_price(&garden, &garden_regions);
     println!("{}", part_one);
@@ -200,6 +234,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::garden_editor::EditableGarden;
 
     #[test]
     fn part_one_test() {
@@ -217,7 +252,7 @@ mod tests {
         ];
 
         let garden = parse_garden(&input);
-        let garden_regions = parse_garden_regions(&garden);
+        let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
 
         assert_eq!(fence_price(&garden, &garden_regions), 1930);
     }
@@ -232,7 +267,7 @@ mod tests {
         ];
 
         let garden = parse_garden(&input);
-        let garden_regions = parse_garden_regions(&garden);
+        let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
 
         assert_eq!(fence_price_by_face(&garden_regions), 80);
     }
@@ -248,7 +283,7 @@ mod tests {
         ];
 
         let garden = parse_garden(&input);
-        let garden_regions = parse_garden_regions(&garden);
+        let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
 
         assert_eq!(fence_price_by_face(&garden_regions), 236);
     }
@@ -265,8 +300,145 @@ mod tests {
         ];
 
         let garden = parse_garden(&input);
-        let garden_regions = parse_garden_regions(&garden);
+        let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
 
         assert_eq!(fence_price_by_face(&garden_regions), 368);
     }
+
+    #[test]
+    fn editable_garden_matches_full_reprice_test() {
+        let input = vec![
+            "RRRRIICCFF".to_string(),
+            "RRRRIICCCF".to_string(),
+            "VVRRRCCFFF".to_string(),
+            "VVRCCCJFFF".to_string(),
+            "VVVVCJJCFE".to_string(),
+            "VVIVCCJJEE".to_string(),
+            "VVIIICJJEE".to_string(),
+            "MIIIIIJJEE".to_string(),
+            "MIIISIJEEE".to_string(),
+            "MMMISSJEEE".to_string(),
+        ];
+
+        let mut garden = parse_garden(&input);
+        let mut editable = EditableGarden::new(garden.clone());
+
+        assert_eq!(editable.total_price(), 1930);
+        assert_eq!(editable.total_price_by_face(), 1206);
+
+        let edits = [(4, 1, 'C'), (2, 2, 'V'), (6, 4, 'X'), (6, 4, 'J'), (9, 9, 'R'), (0, 0, 'V')];
+        for (x, y, plant) in edits {
+            assert!(editable.set(x, y, plant));
+            garden[y][x] = plant;
+
+            let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
+            assert_eq!(editable.total_price(), fence_price(&garden, &garden_regions));
+            assert_eq!(editable.total_price_by_face(), fence_price_by_face(&garden_regions));
+            assert_eq!(editable.regions().count(), garden_regions.len());
+        }
+    }
+
+    #[test]
+    fn editable_garden_split_and_merge_test() {
+        let input = vec![
+            "AAA".to_string(),
+            "ABA".to_string(),
+            "AAA".to_string(),
+        ];
+
+        let mut editable = EditableGarden::new(parse_garden(&input));
+        assert_eq!(editable.regions().count(), 2);
+
+        // Cutting the ring on both sides splits it in two
+        editable.set(1, 0, 'B');
+        editable.set(1, 2, 'B');
+        assert_eq!(editable.regions().count(), 3);
+        assert_eq!(editable.region_at(0, 0).unwrap().area(), 3);
+        assert_eq!(editable.region_at(1, 1).unwrap().area(), 3);
+
+        // Restoring the middle plot merges everything back together
+        editable.set(1, 0, 'A');
+        editable.set(1, 2, 'A');
+        editable.set(1, 1, 'A');
+        assert_eq!(editable.regions().count(), 1);
+        assert_eq!(editable.total_price(), 9 * 12);
+        assert_eq!(editable.total_price_by_face(), 9 * 4);
+
+        assert!(!editable.set(3, 0, 'A'));
+    }
+
+    #[test]
+    fn editable_garden_region_order_test() {
+        let input = vec![
+            "RRRRIICCFF".to_string(),
+            "RRRRIICCCF".to_string(),
+            "VVRRRCCFFF".to_string(),
+            "VVRCCCJFFF".to_string(),
+            "VVVVCJJCFE".to_string(),
+            "VVIVCCJJEE".to_string(),
+            "VVIIICJJEE".to_string(),
+            "MIIIIIJJEE".to_string(),
+            "MIIISIJEEE".to_string(),
+            "MMMISSJEEE".to_string(),
+        ];
+
+        for connectivity in [Connectivity::Four, Connectivity::Eight] {
+            let mut garden = parse_garden(&input);
+            let mut editable = EditableGarden::with_connectivity(garden.clone(), connectivity);
+
+            for (x, y, plant) in [(0, 0, 'A'), (4, 1, 'C'), (6, 4, 'X'), (9, 9, 'R'), (0, 0, 'R')] {
+                editable.set(x, y, plant);
+                garden[y][x] = plant;
+
+                // same regions, in the same order, as a fresh parse
+                let mut expected = parse_garden_regions(&garden, connectivity);
+                for region in &mut expected {
+                    region.sort_by_key(|&(x, y)| (y, x));
+                }
+                let regions: Vec<Vec<(usize, usize)>> = editable.regions().map(|region| region.cells.clone()).collect();
+                assert_eq!(regions, expected);
+                assert_eq!(editable.total_price(), fence_price(&garden, &expected));
+                assert_eq!(editable.total_price_by_face(), fence_price_by_face(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn eight_connected_regions_test() {
+        let input = vec![
+            "ABA".to_string(),
+            "BAB".to_string(),
+            "ABA".to_string(),
+        ];
+
+        let garden = parse_garden(&input);
+
+        let four_connected = parse_garden_regions(&garden, Connectivity::Four);
+        assert_eq!(four_connected.len(), 9);
+        assert_eq!(fence_price(&garden, &four_connected), 36);
+        assert_eq!(fence_price_by_face(&four_connected), 36);
+
+        let eight_connected = parse_garden_regions(&garden, Connectivity::Eight);
+        assert_eq!(eight_connected.len(), 2);
+        assert_eq!(eight_connected[0].len(), 5);
+        assert_eq!(eight_connected[1].len(), 4);
+        assert_eq!(fence_price(&garden, &eight_connected), 5 * 20 + 4 * 16);
+        assert_eq!(fence_price_by_face(&eight_connected), 5 * 20 + 4 * 16);
+    }
+
+    #[test]
+    fn region_order_is_deterministic_test() {
+        let input = vec![
+            "AAAA".to_string(),
+            "BBCD".to_string(),
+            "BBCC".to_string(),
+            "EEEC".to_string(),
+        ];
+
+        let garden = parse_garden(&input);
+        let garden_regions = parse_garden_regions(&garden, Connectivity::Four);
+
+        let first_cells: Vec<(usize, usize)> = garden_regions.iter().map(|region| region[0]).collect();
+        assert_eq!(first_cells, vec![(0, 0), (0, 1), (2, 1), (3, 1), (0, 3)]);
+    }
 }